@@ -13,7 +13,7 @@ async fn main() -> Result<(), Error> {
 
     let t = match Tarkov::login(email, password, &hwid).await {
         Ok(t) => Ok(t),
-        Err(Error::LoginError(e)) => match e {
+        Err(Error::LoginError(e, _)) => match e {
             // 2FA required!
             LoginError::TwoFactorRequired => {
                 // Get 2FA from email (or generate TOTP) then continue...
@@ -25,7 +25,7 @@ async fn main() -> Result<(), Error> {
         Err(e) => Err(e),
     }?;
 
-    println!("{}", t.session);
+    println!("{}", t.session());
 
     Ok(())
 }
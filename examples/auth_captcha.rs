@@ -13,7 +13,7 @@ async fn main() -> Result<(), Error> {
 
     let t = match Tarkov::login(email, password, &hwid).await {
         Ok(t) => Ok(t),
-        Err(Error::LoginError(e)) => match e {
+        Err(Error::LoginError(e, _)) => match e {
             // Captcha required!
             LoginError::CaptchaRequired => {
                 // Solve captcha here and try again
@@ -25,7 +25,7 @@ async fn main() -> Result<(), Error> {
         Err(e) => Err(e),
     }?;
 
-    println!("{}", t.session);
+    println!("{}", t.session());
 
     Ok(())
 }
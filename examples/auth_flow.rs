@@ -0,0 +1,43 @@
+use tarkov::hwid::generate_hwid;
+use tarkov::login::{LoginFlow, LoginState, QueueUpdate};
+use tarkov::Error;
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    std::env::set_var("RUST_LOG", "tarkov=info");
+    env_logger::init();
+
+    let mut flow = LoginFlow::new("me@example.com", "password", &generate_hwid());
+    let mut state = flow.submit().await?;
+    loop {
+        state = match state {
+            // Solve captcha here and continue...
+            LoginState::CaptchaRequired => {
+                flow.captcha("03AOLTBLQ952pO-qQYPeLr53N5nK9Co14iXyCp...")
+                    .await?
+            }
+            // Get 2FA from email (or generate TOTP) then continue...
+            LoginState::TwoFactorRequired => flow.two_factor("XYZ").await?,
+            _ => break,
+        };
+    }
+
+    let t = if state == LoginState::Queued {
+        let mut updates = flow.wait_queue();
+        loop {
+            match updates.recv().await {
+                Some(Ok(QueueUpdate::Waiting(status))) => {
+                    println!("Queue position {}, ETA {:?}", status.position, status.eta())
+                }
+                Some(Ok(QueueUpdate::Ready(t))) => break *t,
+                Some(Err(e)) => return Err(e),
+                None => unreachable!(),
+            }
+        }
+    } else {
+        flow.finish()?
+    };
+    println!("{}", t.session());
+
+    Ok(())
+}
@@ -1,19 +1,17 @@
-use crate::{
-    handle_error, handle_error2, Error, ErrorResponse, Result, Tarkov, GAME_VERSION,
-    LAUNCHER_ENDPOINT, LAUNCHER_VERSION, PROD_ENDPOINT,
-};
+use crate::config::ClientConfig;
+use crate::transport::Transport;
+use crate::{handle_error, handle_error2, Error, ErrorResponse, Result, Tarkov};
 use flate2::read::ZlibDecoder;
-use hyper::body::Buf;
-use hyper::client::connect::dns::GaiResolver;
-use hyper::client::{Client, HttpConnector};
+use hyper::Method;
 use hyper::Request;
 use hyper::StatusCode;
-use hyper::{Body, Method};
-use hyper_tls::HttpsConnector;
-use log::debug;
+use log::{debug, info, warn};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::io::Read;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot::{self, error::TryRecvError};
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -25,6 +23,7 @@ struct LoginRequest<'a> {
 }
 
 #[derive(Debug, Deserialize)]
+#[allow(dead_code)]
 pub(crate) struct Auth {
     pub aid: String,
     pub lang: String,
@@ -61,6 +60,9 @@ pub enum LoginError {
     /// Captcha response is required to continue authentication.
     #[error(display = "captcha is required")]
     CaptchaRequired,
+    /// Incorrect or expired captcha response.
+    #[error(display = "incorrect captcha")]
+    BadCaptcha,
     /// Incorrect 2FA code.
     #[error(display = "incorrect 2FA code")]
     BadTwoFactorCode,
@@ -72,18 +74,35 @@ pub enum LoginError {
     WrongMajorVersion,
 }
 
+impl LoginError {
+    /// Map a backend error code of the launcher API.
+    pub(crate) fn from_code(code: u64) -> Option<Self> {
+        match code {
+            206 => Some(LoginError::BadLogin),
+            209 => Some(LoginError::TwoFactorRequired),
+            211 => Some(LoginError::BadTwoFactorCode),
+            214 => Some(LoginError::CaptchaRequired),
+            216 | 218 => Some(LoginError::BadCaptcha),
+            219 | 230 => Some(LoginError::RateLimited),
+            232 => Some(LoginError::WrongMajorVersion),
+            _ => None,
+        }
+    }
+}
+
 pub(crate) async fn login(
-    client: &Client<HttpsConnector<HttpConnector<GaiResolver>>, Body>,
+    transport: &dyn Transport,
+    config: &ClientConfig,
     email: &str,
     password: &str,
     captcha: Option<&str>,
     hwid: &str,
 ) -> Result<Auth> {
     let url = format!(
-        "{}/launcher/login?launcherVersion={}&branch=live",
-        LAUNCHER_ENDPOINT, LAUNCHER_VERSION
+        "{}/launcher/login?launcherVersion={}&branch={}",
+        config.launcher_endpoint, config.launcher_version, config.branch
     );
-    let password = format!("{:x}", md5::compute(&password));
+    let password = format!("{:x}", md5::compute(password));
 
     let body = LoginRequest {
         email,
@@ -92,7 +111,7 @@ pub(crate) async fn login(
         captcha,
     };
 
-    let res: LoginResponse = post_json(client, &url, &body).await?;
+    let res: LoginResponse = post_json(transport, config, &url, None, &body).await?;
     handle_error2(res.error)?;
 
     Ok(Deserialize::deserialize(res.data)?)
@@ -113,14 +132,15 @@ struct SecurityLoginResponse {
 }
 
 pub(crate) async fn activate_hardware(
-    client: &Client<HttpsConnector<HttpConnector<GaiResolver>>, Body>,
+    transport: &dyn Transport,
+    config: &ClientConfig,
     email: &str,
     code: &str,
     hwid: &str,
 ) -> Result<()> {
     let url = format!(
         "{}/launcher/hardwareCode/activate?launcherVersion={}",
-        LAUNCHER_ENDPOINT, LAUNCHER_VERSION
+        config.launcher_endpoint, config.launcher_version
     );
 
     let body = SecurityLoginRequest {
@@ -129,7 +149,7 @@ pub(crate) async fn activate_hardware(
         activate_code: code,
     };
 
-    let res: SecurityLoginResponse = post_json(client, &url, &body).await?;
+    let res: SecurityLoginResponse = post_json(transport, config, &url, None, &body).await?;
     handle_error2(res.error)
 }
 
@@ -158,73 +178,238 @@ struct ExchangeResponse {
 /// Authenticated user session.
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct Session {
-    queued: bool,
+    /// Waiting in the login queue, the session can't be used until it is cleared.
+    pub queued: bool,
     /// Session cookie.
     pub session: String,
 }
 
 pub(crate) async fn exchange_access_token(
-    client: &Client<HttpsConnector<HttpConnector<GaiResolver>>, Body>,
+    transport: &dyn Transport,
+    config: &ClientConfig,
     access_token: &str,
     hwid: &str,
 ) -> Result<Session> {
     let url = format!(
-        "{}/launcher/game/start?launcherVersion={}&branch=live",
-        PROD_ENDPOINT, LAUNCHER_VERSION
+        "{}/launcher/game/start?launcherVersion={}&branch={}",
+        config.prod_endpoint, config.launcher_version, config.branch
     );
 
     let body = ExchangeRequest {
         version: ExchangeVersion {
-            major: GAME_VERSION,
-            game: "live",
+            major: &config.game_version,
+            game: &config.branch,
             backend: "6",
         },
         hw_code: hwid,
     };
 
-    debug!("Sending request to {} ({:?})", url, body);
-    let req = Request::builder()
-        .uri(url)
-        .method(Method::POST)
-        .header("Content-Type", "application/json")
-        .header("User-Agent", format!("BSG Launcher {}", LAUNCHER_VERSION))
-        .header("Authorization", access_token)
-        .body(Body::from(serde_json::to_string(&body)?))?;
-    let res = client.request(req).await?;
+    let res: ExchangeResponse =
+        post_json(transport, config, &url, Some(access_token), &body).await?;
+    handle_error(res.error, res.data)
+}
 
-    match res.status() {
-        StatusCode::OK => {
-            let body = hyper::body::to_bytes(res.into_body()).await?;
-            let mut decode = ZlibDecoder::new(body.bytes());
-            let mut body = String::new();
-            decode.read_to_string(&mut body)?;
-            debug!("Response: {}", body);
+/// Exchange an access token for a session. If the server rejects the game version, retry once
+/// with `game_version`, advertised on login, or the version of the launcher. The accepted
+/// version is kept in `config`.
+pub(crate) async fn start_game(
+    transport: &dyn Transport,
+    config: &mut ClientConfig,
+    access_token: &str,
+    hwid: &str,
+    game_version: Option<&str>,
+) -> Result<Session> {
+    match exchange_access_token(transport, config, access_token, hwid).await {
+        Err(Error::LoginError(LoginError::WrongMajorVersion, _)) => {
+            let version = match game_version {
+                Some(version) if version != config.game_version => version.to_string(),
+                _ => get_game_version(transport, config).await?,
+            };
+            if version == config.game_version {
+                return Err(LoginError::WrongMajorVersion)?;
+            }
 
-            let res = serde_json::from_slice::<ExchangeResponse>(body.as_bytes())?;
-            handle_error(res.error, res.data)
+            info!(
+                "Game version {} was rejected, retrying with {}",
+                config.game_version, version
+            );
+            config.game_version = version;
+            exchange_access_token(transport, config, access_token, hwid).await
         }
-        _ => Err(Error::Status(res.status())),
+        res => res,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct QueueStatusResponse {
+    #[serde(flatten)]
+    error: ErrorResponse,
+    data: Option<QueueStatus>,
+}
+
+/// Position in the login queue.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueStatus {
+    /// Still waiting in the queue.
+    pub queued: bool,
+    /// Position in the queue.
+    pub position: u64,
+    /// Estimated wait in seconds.
+    pub wait_time: u64,
+}
+
+impl QueueStatus {
+    /// Estimated time until the session is ready.
+    pub fn eta(&self) -> Duration {
+        Duration::from_secs(self.wait_time)
+    }
+
+    /// How long to wait before polling again: half the ETA, between 1 and 30 seconds.
+    pub fn poll_interval(&self) -> Duration {
+        (self.eta() / 2)
+            .max(Duration::from_secs(1))
+            .min(Duration::from_secs(30))
+    }
+}
+
+pub(crate) async fn get_queue_status(
+    transport: &dyn Transport,
+    config: &ClientConfig,
+    access_token: &str,
+) -> Result<QueueStatus> {
+    let url = format!(
+        "{}/launcher/queue/status?launcherVersion={}&branch={}",
+        config.prod_endpoint, config.launcher_version, config.branch
+    );
+
+    let res: QueueStatusResponse =
+        post_json(transport, config, &url, Some(access_token), &{}).await?;
+    handle_error(res.error, res.data)
+}
+
+/// Poll the login queue until the session is active.
+pub(crate) async fn wait_in_queue(
+    transport: &dyn Transport,
+    config: &mut ClientConfig,
+    access_token: &str,
+    hwid: &str,
+) -> Result<Session> {
+    loop {
+        let status = get_queue_status(transport, config, access_token).await?;
+        if !status.queued {
+            let session = start_game(transport, config, access_token, hwid, None).await?;
+            if !session.queued {
+                return Ok(session);
+            }
+        }
+
+        info!(
+            "Waiting in login queue at position {}, ETA {:?}",
+            status.position,
+            status.eta()
+        );
+        tokio::time::delay_for(status.poll_interval()).await;
     }
 }
 
+#[derive(Debug, Serialize)]
+struct RefreshRequest<'a> {
+    grant_type: &'a str,
+    refresh_token: &'a str,
+    client_id: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct RefreshResponse {
+    #[serde(flatten)]
+    error: ErrorResponse,
+    data: Option<Token>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Token {
+    pub expires_in: u64,
+    pub access_token: String,
+    pub refresh_token: String,
+}
+
+/// Get a new access token with a refresh token.
+pub(crate) async fn refresh_token(
+    transport: &dyn Transport,
+    config: &ClientConfig,
+    refresh_token: &str,
+) -> Result<Token> {
+    let url = format!(
+        "{}/launcher/token/refresh?launcherVersion={}&branch={}",
+        config.launcher_endpoint, config.launcher_version, config.branch
+    );
+
+    let body = RefreshRequest {
+        grant_type: "refresh_token",
+        refresh_token,
+        client_id: 0,
+    };
+
+    let res: RefreshResponse = post_json(transport, config, &url, None, &body).await?;
+    handle_error(res.error, res.data)
+}
+
+#[derive(Debug, Deserialize)]
+struct DistribResponse {
+    #[serde(flatten)]
+    error: ErrorResponse,
+    data: Option<Distrib>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Distrib {
+    version: String,
+}
+
+/// Get the current game version from the launcher.
+pub(crate) async fn get_game_version(
+    transport: &dyn Transport,
+    config: &ClientConfig,
+) -> Result<String> {
+    let url = format!(
+        "{}/launcher/GetDistrib?launcherVersion={}&branch={}",
+        config.launcher_endpoint, config.launcher_version, config.branch
+    );
+
+    let res: DistribResponse = post_json(transport, config, &url, None, &{}).await?;
+    handle_error(res.error, res.data).map(|d| d.version)
+}
+
 async fn post_json<S: serde::Serialize + ?Sized + std::fmt::Debug, T: DeserializeOwned>(
-    client: &Client<HttpsConnector<HttpConnector<GaiResolver>>, Body>,
+    transport: &dyn Transport,
+    config: &ClientConfig,
     url: &str,
+    access_token: Option<&str>,
     body: &S,
 ) -> Result<T> {
     debug!("Sending request to {} ({:?})", url, body);
-    let req = Request::builder()
+    let mut req = Request::builder()
         .uri(url)
         .method(Method::POST)
         .header("Content-Type", "application/json")
-        .header("User-Agent", format!("BSG Launcher {}", LAUNCHER_VERSION))
-        .body(Body::from(serde_json::to_string(&body)?))?;
-    let res = client.request(req).await?;
+        .header(
+            "User-Agent",
+            format!("BSG Launcher {}", config.launcher_version),
+        );
+    if let Some(access_token) = access_token {
+        req = req.header("Authorization", access_token);
+    }
+    let req = req.body(match serde_json::to_string(&body)?.as_str() {
+        "null" => b"{}".to_vec(),
+        body => body.as_bytes().to_vec(),
+    })?;
+    let res = transport.send(req).await?;
 
     match res.status() {
         StatusCode::OK => {
-            let body = hyper::body::to_bytes(res.into_body()).await?;
-            let mut decode = ZlibDecoder::new(body.bytes());
+            let mut decode = ZlibDecoder::new(res.body().as_slice());
             let mut body = String::new();
             decode.read_to_string(&mut body)?;
             debug!("Response: {}", body);
@@ -238,12 +423,50 @@ async fn post_json<S: serde::Serialize + ?Sized + std::fmt::Debug, T: Deserializ
 impl Tarkov {
     /// Keep the current session alive. Session expires after 30 seconds of idling.
     pub async fn keep_alive(&self) -> Result<()> {
-        let url = format!("{}/client/game/keepalive", PROD_ENDPOINT);
+        let url = format!("{}/client/game/keepalive", self.config.prod_endpoint);
         let res: ErrorResponse = self.post_json(&url, &{}).await?;
 
-        match res.code {
-            0 => Ok(()),
-            _ => Err(Error::UnknownAPIError(res.code)),
-        }
+        handle_error2(res)
+    }
+
+    /// Spawn a background task that calls `keep_alive` whenever no request was sent for
+    /// `interval`. An interval of 20 seconds is safe.
+    ///
+    /// Failures are sent to the returned channel, the task keeps running regardless. The task
+    /// stops when the client is dropped or `stop_keep_alive` is called. Replaces any previously
+    /// spawned task. Must be called from within a tokio runtime.
+    pub fn spawn_keep_alive(&mut self, interval: Duration) -> mpsc::UnboundedReceiver<Error> {
+        let (stop, mut stopped) = oneshot::channel();
+        let (errors, rx) = mpsc::unbounded_channel();
+        let client = self.fork();
+
+        tokio::spawn(async move {
+            loop {
+                let idle = client.last_request.lock().unwrap().elapsed();
+                if idle < interval {
+                    tokio::time::delay_for(interval - idle).await;
+                }
+
+                if let Err(TryRecvError::Closed) = stopped.try_recv() {
+                    debug!("Keep-alive task stopped");
+                    break;
+                }
+
+                if client.last_request.lock().unwrap().elapsed() >= interval {
+                    if let Err(e) = client.keep_alive().await {
+                        warn!("Keep-alive failed: {}", e);
+                        let _ = errors.send(e);
+                    }
+                }
+            }
+        });
+
+        self.keep_alive = Some(stop);
+        rx
+    }
+
+    /// Stop the background keep-alive task, if any.
+    pub fn stop_keep_alive(&mut self) {
+        self.keep_alive = None;
     }
 }
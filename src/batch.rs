@@ -0,0 +1,438 @@
+use crate::inventory::{
+    ActionError, ActionErrors, BarterItem, Container, InventoryUpdate, MoveItemRequest,
+    MoveItemResponse, RagfairResponseData,
+};
+use crate::ragfair::{BuyItemRequest, BuyOffer, OfferItemRequest, Requirement, SellRequirement};
+use crate::trading::{SellItem, SellItemRequest, TradeItemRequest};
+use crate::{handle_error2, Error, Result, Tarkov};
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum Action {
+    Trade(TradeItemRequest),
+    Sell(SellItemRequest),
+    BuyOffer(BuyItemRequest),
+    AddOffer(OfferItemRequest),
+    Move(MoveAction),
+    Split(SplitAction),
+    Merge(MergeAction),
+    Transfer(TransferAction),
+    Toggle(ToggleAction),
+    Item(ItemAction),
+    Tag(TagAction),
+    Bind(BindAction),
+    ReadEncyclopedia(ReadEncyclopediaAction),
+}
+
+#[derive(Debug, Serialize)]
+struct MoveAction {
+    #[serde(rename = "Action")]
+    action: &'static str,
+    item: String,
+    to: Container,
+}
+
+#[derive(Debug, Serialize)]
+struct SplitAction {
+    #[serde(rename = "Action")]
+    action: &'static str,
+    item: String,
+    container: Container,
+    count: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct MergeAction {
+    #[serde(rename = "Action")]
+    action: &'static str,
+    item: String,
+    with: String,
+}
+
+#[derive(Debug, Serialize)]
+struct TransferAction {
+    #[serde(rename = "Action")]
+    action: &'static str,
+    item: String,
+    with: String,
+    count: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct ToggleAction {
+    #[serde(rename = "Action")]
+    action: &'static str,
+    item: String,
+    value: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ItemAction {
+    #[serde(rename = "Action")]
+    action: &'static str,
+    item: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct TagAction {
+    action: &'static str,
+    #[serde(rename = "item")]
+    item: String,
+    tag_name: String,
+    tag_color: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct BindAction {
+    #[serde(rename = "Action")]
+    action: &'static str,
+    item: String,
+    index: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct ReadEncyclopediaAction {
+    #[serde(rename = "Action")]
+    action: &'static str,
+    ids: Vec<String>,
+}
+
+/// Item moving actions sent together in a single request.
+///
+/// Actions are applied in the order they were added. Use `Tarkov::send_batch` to send them.
+///
+/// ```no_run
+/// # async fn run(t: tarkov::Tarkov) -> tarkov::Result<()> {
+/// use tarkov::batch::ActionBatch;
+///
+/// let mut batch = ActionBatch::new();
+/// batch
+///     .sell("54cb50c76803fa8b248b4571", "5e8e1c8c9c2e6e1a5c3f1c01", 1)
+///     .sell("54cb50c76803fa8b248b4571", "5e8e1c8c9c2e6e1a5c3f1c02", 1);
+///
+/// let res = t.send_batch(&batch).await?;
+/// for e in &res.errors {
+///     println!("action {:?} failed: {}", e.index, e.error);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct ActionBatch {
+    actions: Vec<Action>,
+}
+
+impl ActionBatch {
+    /// Create an empty batch.
+    pub fn new() -> Self {
+        ActionBatch::default()
+    }
+
+    /// Number of actions in the batch.
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    /// Returns `true` if the batch has no actions.
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// Buy an item from a trader, see `Tarkov::trade_item`.
+    pub fn trade(
+        &mut self,
+        trader_id: &str,
+        item_id: &str,
+        quantity: u64,
+        barter_items: &[BarterItem],
+    ) -> &mut Self {
+        self.push(Action::Trade(TradeItemRequest {
+            action: "TradingConfirm",
+            trade_type: "buy_from_trader",
+            trader_id: trader_id.to_string(),
+            item_id: item_id.to_string(),
+            count: quantity,
+            scheme_id: 0,
+            scheme_items: barter_items.to_vec(),
+        }))
+    }
+
+    /// Sell an item to a trader, see `Tarkov::sell_item`.
+    pub fn sell(&mut self, trader_id: &str, item_id: &str, quantity: u64) -> &mut Self {
+        self.push(Action::Sell(SellItemRequest {
+            action: "TradingConfirm",
+            trade_type: "sell_to_trader",
+            trader_id: trader_id.to_string(),
+            items: vec![SellItem {
+                id: item_id.to_string(),
+                count: quantity,
+                scheme_id: 0,
+            }],
+        }))
+    }
+
+    /// Buy a flea market offer, see `Tarkov::buy_item`.
+    pub fn buy_offer(
+        &mut self,
+        offer_id: &str,
+        quantity: u64,
+        barter_items: &[BarterItem],
+    ) -> &mut Self {
+        self.push(Action::BuyOffer(BuyItemRequest {
+            action: "RagFairBuyOffer",
+            offers: vec![BuyOffer {
+                id: offer_id.to_string(),
+                count: quantity,
+                items: barter_items.to_vec(),
+            }],
+        }))
+    }
+
+    /// List items on the flea market, see `Tarkov::offer_item`.
+    pub fn add_offer(
+        &mut self,
+        items: &[&str],
+        requirements: &[Requirement],
+        sell_all: bool,
+    ) -> &mut Self {
+        self.push(Action::AddOffer(OfferItemRequest {
+            action: "RagFairAddOffer",
+            sell_in_one_piece: sell_all,
+            items: items.iter().map(|id| id.to_string()).collect(),
+            requirements: requirements
+                .iter()
+                .map(|r| SellRequirement {
+                    requirement: r.to_owned(),
+                    level: 0,
+                    side: 0,
+                    only_functional: false,
+                })
+                .collect(),
+        }))
+    }
+
+    /// Move an item, see `Tarkov::move_item`.
+    pub fn move_item(&mut self, item_id: &str, to: &Container) -> &mut Self {
+        self.push(Action::Move(MoveAction {
+            action: "Move",
+            item: item_id.to_string(),
+            to: to.clone(),
+        }))
+    }
+
+    /// Split `count` items off a stack, see `Tarkov::split_item`.
+    pub fn split(&mut self, item_id: &str, to: &Container, count: u64) -> &mut Self {
+        self.push(Action::Split(SplitAction {
+            action: "Split",
+            item: item_id.to_string(),
+            container: to.clone(),
+            count,
+        }))
+    }
+
+    /// Merge a stack into another, see `Tarkov::merge_item`.
+    pub fn merge(&mut self, item_id: &str, with_id: &str) -> &mut Self {
+        self.push(Action::Merge(MergeAction {
+            action: "Merge",
+            item: item_id.to_string(),
+            with: with_id.to_string(),
+        }))
+    }
+
+    /// Move `count` items between stacks, see `Tarkov::transfer_item`.
+    pub fn transfer(&mut self, item_id: &str, with_id: &str, count: u64) -> &mut Self {
+        self.push(Action::Transfer(TransferAction {
+            action: "Transfer",
+            item: item_id.to_string(),
+            with: with_id.to_string(),
+            count,
+        }))
+    }
+
+    /// Fold or unfold a stock, see `Tarkov::fold_item`.
+    pub fn fold(&mut self, item_id: &str, folded: bool) -> &mut Self {
+        self.push(Action::Toggle(ToggleAction {
+            action: "Fold",
+            item: item_id.to_string(),
+            value: folded,
+        }))
+    }
+
+    /// Turn a device on or off, see `Tarkov::toggle_item`.
+    pub fn toggle(&mut self, item_id: &str, on: bool) -> &mut Self {
+        self.push(Action::Toggle(ToggleAction {
+            action: "Toggle",
+            item: item_id.to_string(),
+            value: on,
+        }))
+    }
+
+    /// Examine an item, see `Tarkov::examine_item`.
+    pub fn examine(&mut self, item_id: &str) -> &mut Self {
+        self.push(Action::Item(ItemAction {
+            action: "Examine",
+            item: item_id.to_string(),
+        }))
+    }
+
+    /// Discard an item, see `Tarkov::remove_item`.
+    pub fn remove(&mut self, item_id: &str) -> &mut Self {
+        self.push(Action::Item(ItemAction {
+            action: "Remove",
+            item: item_id.to_string(),
+        }))
+    }
+
+    /// Name and color a container, see `Tarkov::tag_item`.
+    pub fn tag(&mut self, item_id: &str, name: &str, color: u64) -> &mut Self {
+        self.push(Action::Tag(TagAction {
+            action: "Tag",
+            item: item_id.to_string(),
+            tag_name: name.to_string(),
+            tag_color: color,
+        }))
+    }
+
+    /// Bind an item to a quick access slot, see `Tarkov::bind_item`.
+    pub fn bind(&mut self, item_id: &str, index: u64) -> &mut Self {
+        self.push(Action::Bind(BindAction {
+            action: "Bind",
+            item: item_id.to_string(),
+            index,
+        }))
+    }
+
+    /// Mark item templates as read, see `Tarkov::read_encyclopedia`.
+    pub fn read_encyclopedia(&mut self, schema_ids: &[&str]) -> &mut Self {
+        self.push(Action::ReadEncyclopedia(ReadEncyclopediaAction {
+            action: "ReadEncyclopedia",
+            ids: schema_ids.iter().map(|id| id.to_string()).collect(),
+        }))
+    }
+
+    fn push(&mut self, action: Action) -> &mut Self {
+        self.actions.push(action);
+        self
+    }
+}
+
+/// Result of `Tarkov::send_batch`.
+#[derive(Debug)]
+pub struct BatchResponse {
+    /// Inventory changes of every successful action.
+    pub update: InventoryUpdate,
+    /// Actions rejected by the API.
+    pub errors: Vec<ActionError>,
+    /// Transaction number (`tm`) the batch was sent with.
+    pub tm: u64,
+}
+
+impl BatchResponse {
+    /// Error of the action at `index`, `None` if the action succeeded. Errors without an index
+    /// are only listed in `errors`.
+    pub fn error(&self, index: usize) -> Option<&Error> {
+        self.errors
+            .iter()
+            .find(|e| e.index == Some(index))
+            .map(|e| &e.error)
+    }
+
+    /// Returns `true` if every action succeeded.
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Inventory changes, or `Error::ActionErrors` if any action was rejected.
+    pub fn into_result(self) -> Result<InventoryUpdate> {
+        if self.errors.is_empty() {
+            Ok(self.update)
+        } else {
+            Err(Error::ActionErrors(ActionErrors(self.errors)))
+        }
+    }
+}
+
+impl Tarkov {
+    /// Send every action of `batch` in a single request.
+    ///
+    /// Rejected actions do not fail the request, they are listed in `BatchResponse::errors`.
+    ///
+    /// Every batch is numbered with the next transaction number (`tm`) of the client, like the
+    /// game does. Concurrent batches are sent one at a time, in `tm` order. Flea market searches
+    /// share the numbering but are not held back by pending batches.
+    ///
+    /// Single action methods like `sell_item` or `move_item` do not return their `tm`, send a
+    /// batch with one action to match a response with its transaction number.
+    pub async fn send_batch(&self, batch: &ActionBatch) -> Result<BatchResponse> {
+        if batch.is_empty() {
+            return Err(Error::InvalidParameters);
+        }
+
+        let (tm, mut res) = self.post_actions(&batch.actions).await?;
+        let errors = res.action_errors();
+        let update = Deserialize::deserialize(res.items)?;
+
+        Ok(BatchResponse { update, errors, tm })
+    }
+
+    /// Send a batch with a single action. A rejected action is returned as its own error.
+    ///
+    /// The `tm` is dropped to keep the single action methods returning an `InventoryUpdate`,
+    /// see `send_batch`.
+    pub(crate) async fn send_single(&self, batch: ActionBatch) -> Result<InventoryUpdate> {
+        let (_, mut res) = self.post_actions(&batch.actions).await?;
+        res.handle_errors()?;
+
+        Ok(Deserialize::deserialize(res.items)?)
+    }
+
+    async fn post_actions(&self, actions: &[Action]) -> Result<(u64, RagfairResponseData)> {
+        let url = format!(
+            "{}/client/game/profile/items/moving",
+            self.config.prod_endpoint
+        );
+
+        // Held until the response arrives, so the server sees transactions in order.
+        let _moving = self.moving.lock().await;
+        let tm = self.next_tm();
+        debug!("Sending {} item moving actions, tm {}", actions.len(), tm);
+
+        let body = MoveItemRequest { data: actions, tm };
+        let res: MoveItemResponse = self.post_json(&url, &body).await?;
+        handle_error2(res.error)?;
+
+        Ok((tm, Deserialize::deserialize(res.data)?))
+    }
+}
+
+#[test]
+fn test_action_batch() {
+    let mut batch = ActionBatch::new();
+    batch
+        .sell("trader", "item", 2)
+        .merge("item", "stack")
+        .move_item("item", &Container::slot("rifle", "mod_scope").unwrap())
+        .tag("case", "keys", 2);
+    assert_eq!(batch.len(), 4);
+
+    let body = serde_json::to_value(MoveItemRequest {
+        data: &batch.actions,
+        tm: 0,
+    })
+    .unwrap();
+    assert_eq!(body["data"][0]["type"], "sell_to_trader");
+    assert_eq!(body["data"][1]["Action"], "Merge");
+    assert_eq!(body["data"][2]["to"]["container"], "mod_scope");
+    assert!(body["data"][2]["to"].get("location").is_none());
+    assert_eq!(body["data"][3]["Action"], "Tag");
+    assert_eq!(body["data"][3]["TagName"], "keys");
+    assert_eq!(body["data"][3]["item"], "case");
+
+    // Grids and equipment slots have their own constructors.
+    assert!(Container::slot("stash", "hideout").is_err());
+    assert!(Container::slot("equipment", "Backpack").is_err());
+    assert!(Container::slot("rifle", "patron_in_weapon").is_ok());
+}
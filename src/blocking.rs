@@ -0,0 +1,324 @@
+use crate::batch::{ActionBatch, BatchResponse};
+use crate::constant::{Item, Localization, Locations, Weather};
+use crate::friend::Friends;
+use crate::inventory::{BarterItem, Container, InventoryUpdate};
+use crate::market_filter::MarketFilter;
+use crate::profile::Profile;
+use crate::ragfair::{Price, Requirement, SearchResult};
+use crate::session::SessionState;
+use crate::trading::{Trader, TraderItem};
+use crate::{Error, Result};
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+use tokio::runtime::{Builder, Runtime};
+use tokio::sync::mpsc;
+
+/// Blocking client for the EFT API.
+///
+/// Wraps the async `tarkov::Tarkov` with a runtime of its own, so it can be used from
+/// synchronous code. Methods must not be called from within an async runtime.
+///
+/// ```no_run
+/// # fn run() -> tarkov::Result<()> {
+/// use tarkov::blocking::Tarkov;
+///
+/// let t = Tarkov::from_session("e1bc65a216325f0ad0db8518fa299db2")?;
+/// let price = t.get_item_price("5448be9a4bdc2dfd2f8b456a")?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Tarkov {
+    inner: crate::Tarkov,
+    runtime: Runtime,
+}
+
+impl Tarkov {
+    /// Run an async constructor, such as `TarkovBuilder::login`, on a new runtime.
+    ///
+    /// ```no_run
+    /// # fn run() -> tarkov::Result<()> {
+    /// use tarkov::blocking;
+    /// use tarkov::hwid::generate_hwid;
+    /// use tarkov::retry::RetryPolicy;
+    ///
+    /// let t = blocking::Tarkov::connect(
+    ///     tarkov::Tarkov::builder()
+    ///         .retry(RetryPolicy::default())
+    ///         .login("me@example.com", "password", &generate_hwid()),
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn connect<F>(future: F) -> Result<Self>
+    where
+        F: Future<Output = Result<crate::Tarkov>>,
+    {
+        let mut runtime = Builder::new().threaded_scheduler().enable_all().build()?;
+        let inner = runtime.block_on(future)?;
+
+        Ok(Tarkov { inner, runtime })
+    }
+
+    /// Login with email and password.
+    pub fn login(email: &str, password: &str, hwid: &str) -> Result<Self> {
+        Tarkov::connect(crate::Tarkov::login(email, password, hwid))
+    }
+
+    /// Login with email, password and captcha.
+    pub fn login_with_captcha(
+        email: &str,
+        password: &str,
+        captcha: &str,
+        hwid: &str,
+    ) -> Result<Self> {
+        Tarkov::connect(crate::Tarkov::login_with_captcha(
+            email, password, captcha, hwid,
+        ))
+    }
+
+    /// Login with email, password and 2FA code.
+    pub fn login_with_2fa(email: &str, password: &str, code: &str, hwid: &str) -> Result<Self> {
+        Tarkov::connect(crate::Tarkov::login_with_2fa(email, password, code, hwid))
+    }
+
+    /// Login with a Bearer token.
+    pub fn from_access_token(access_token: &str, hwid: &str) -> Result<Self> {
+        Tarkov::connect(crate::Tarkov::from_access_token(access_token, hwid))
+    }
+
+    /// Login with a cookie session (AKA `PHPSESSID`).
+    pub fn from_session(session: &str) -> Result<Self> {
+        Tarkov::connect(async move { Ok(crate::Tarkov::from_session(session)) })
+    }
+
+    /// Resume a saved session, see `TarkovBuilder::resume`.
+    pub fn resume(state: SessionState) -> Result<Self> {
+        Tarkov::connect(crate::Tarkov::resume(state))
+    }
+
+    /// Async client used under the hood.
+    pub fn inner(&self) -> &crate::Tarkov {
+        &self.inner
+    }
+
+    /// Session cookie (AKA `PHPSESSID`).
+    pub fn session(&self) -> String {
+        self.inner.session()
+    }
+
+    /// Hardware ID.
+    pub fn hwid(&self) -> String {
+        self.inner.hwid()
+    }
+
+    /// Snapshot of the current session, to be saved and resumed later.
+    pub fn session_state(&self) -> SessionState {
+        self.inner.session_state()
+    }
+
+    /// Exchange the stored access token for a new session, see `Tarkov::renew_session`.
+    pub fn renew_session(&self) -> Result<()> {
+        self.block_on(self.inner.renew_session())
+    }
+
+    /// Keep the current session alive. Session expires after 30 seconds of idling.
+    pub fn keep_alive(&self) -> Result<()> {
+        self.block_on(self.inner.keep_alive())
+    }
+
+    /// Keep the session alive from a background thread of the runtime, see
+    /// `Tarkov::spawn_keep_alive`. Check for failures with `try_recv`.
+    pub fn spawn_keep_alive(&mut self, interval: Duration) -> mpsc::UnboundedReceiver<Error> {
+        let Tarkov { inner, runtime } = self;
+        runtime.enter(|| inner.spawn_keep_alive(interval))
+    }
+
+    /// Stop the background keep-alive task, if any.
+    pub fn stop_keep_alive(&mut self) {
+        self.inner.stop_keep_alive()
+    }
+
+    /// Get a list of account's profiles.
+    pub fn get_profiles(&self) -> Result<Vec<Profile>> {
+        self.block_on(self.inner.get_profiles())
+    }
+
+    /// Select a profile by user ID.
+    pub fn select_profile(&self, user_id: &str) -> Result<()> {
+        self.block_on(self.inner.select_profile(user_id))
+    }
+
+    /// Get a list of account's friends.
+    pub fn get_friends(&self) -> Result<Friends> {
+        self.block_on(self.inner.get_friends())
+    }
+
+    /// Get a list of all in-game items.
+    pub fn get_items(&self) -> Result<HashMap<String, Item>> {
+        self.block_on(self.inner.get_items())
+    }
+
+    /// Get a list of all in-game item prices.
+    pub fn get_item_prices(&self) -> Result<HashMap<String, u64>> {
+        self.block_on(self.inner.get_item_prices())
+    }
+
+    /// Get a list of all locations/maps.
+    pub fn get_locations(&self) -> Result<Locations> {
+        self.block_on(self.inner.get_locations())
+    }
+
+    /// Get the current forecast and time.
+    pub fn get_weather(&self) -> Result<Weather> {
+        self.block_on(self.inner.get_weather())
+    }
+
+    /// Get the localization table. Pass a valid ISO 639-1 language code.
+    pub fn get_i18n(&self, language: &str) -> Result<Localization> {
+        self.block_on(self.inner.get_i18n(language))
+    }
+
+    /// Get a list of all traders.
+    pub fn get_traders(&self) -> Result<Vec<Trader>> {
+        self.block_on(self.inner.get_traders())
+    }
+
+    /// Get a trader by their ID.
+    pub fn get_trader(&self, trader_id: &str) -> Result<Trader> {
+        self.block_on(self.inner.get_trader(trader_id))
+    }
+
+    /// Get a list of items for sale by trader ID.
+    pub fn get_trader_items(&self, trader_id: &str) -> Result<Vec<TraderItem>> {
+        self.block_on(self.inner.get_trader_items(trader_id))
+    }
+
+    /// Trade items with traders, see `Tarkov::trade_item`.
+    pub fn trade_item(
+        &self,
+        trader_id: &str,
+        item_id: &str,
+        quantity: u64,
+        barter_items: &[BarterItem],
+    ) -> Result<InventoryUpdate> {
+        self.block_on(
+            self.inner
+                .trade_item(trader_id, item_id, quantity, barter_items),
+        )
+    }
+
+    /// Sell items to trader.
+    pub fn sell_item(
+        &self,
+        trader_id: &str,
+        item_id: &str,
+        quantity: u64,
+    ) -> Result<InventoryUpdate> {
+        self.block_on(self.inner.sell_item(trader_id, item_id, quantity))
+    }
+
+    /// Search the flea market.
+    pub fn search_market(
+        &self,
+        page: u64,
+        limit: u64,
+        filter: MarketFilter<'_>,
+    ) -> Result<SearchResult> {
+        self.block_on(self.inner.search_market(page, limit, filter))
+    }
+
+    /// Get the item price data from the flea market.
+    pub fn get_item_price(&self, schema_id: &str) -> Result<Price> {
+        self.block_on(self.inner.get_item_price(schema_id))
+    }
+
+    /// Buy items from the flea market.
+    pub fn buy_item(
+        &self,
+        offer_id: &str,
+        quantity: u64,
+        barter_items: &[BarterItem],
+    ) -> Result<InventoryUpdate> {
+        self.block_on(self.inner.buy_item(offer_id, quantity, barter_items))
+    }
+
+    /// List an item for sale on the flea market.
+    pub fn offer_item(
+        &self,
+        items: &[&str],
+        requirements: &[Requirement],
+        sell_all: bool,
+    ) -> Result<InventoryUpdate> {
+        self.block_on(self.inner.offer_item(items, requirements, sell_all))
+    }
+
+    /// Move an item into a slot or grid.
+    pub fn move_item(&self, item_id: &str, to: &Container) -> Result<InventoryUpdate> {
+        self.block_on(self.inner.move_item(item_id, to))
+    }
+
+    /// Split `count` items off a stack into a new stack at `to`.
+    pub fn split_item(&self, item_id: &str, to: &Container, count: u64) -> Result<InventoryUpdate> {
+        self.block_on(self.inner.split_item(item_id, to, count))
+    }
+
+    /// Merge the stack of `item_id` into the stack of `with_id`.
+    pub fn merge_item(&self, item_id: &str, with_id: &str) -> Result<InventoryUpdate> {
+        self.block_on(self.inner.merge_item(item_id, with_id))
+    }
+
+    /// Move `count` items from the stack of `item_id` to the stack of `with_id`.
+    pub fn transfer_item(
+        &self,
+        item_id: &str,
+        with_id: &str,
+        count: u64,
+    ) -> Result<InventoryUpdate> {
+        self.block_on(self.inner.transfer_item(item_id, with_id, count))
+    }
+
+    /// Fold or unfold the stock of a weapon.
+    pub fn fold_item(&self, item_id: &str, folded: bool) -> Result<InventoryUpdate> {
+        self.block_on(self.inner.fold_item(item_id, folded))
+    }
+
+    /// Turn a device, such as a flashlight or night vision, on or off.
+    pub fn toggle_item(&self, item_id: &str, on: bool) -> Result<InventoryUpdate> {
+        self.block_on(self.inner.toggle_item(item_id, on))
+    }
+
+    /// Examine an unknown item.
+    pub fn examine_item(&self, item_id: &str) -> Result<InventoryUpdate> {
+        self.block_on(self.inner.examine_item(item_id))
+    }
+
+    /// Discard an item and everything inside it.
+    pub fn remove_item(&self, item_id: &str) -> Result<InventoryUpdate> {
+        self.block_on(self.inner.remove_item(item_id))
+    }
+
+    /// Name and color a container.
+    pub fn tag_item(&self, item_id: &str, name: &str, color: u64) -> Result<InventoryUpdate> {
+        self.block_on(self.inner.tag_item(item_id, name, color))
+    }
+
+    /// Bind an item to the quick access slot `index`.
+    pub fn bind_item(&self, item_id: &str, index: u64) -> Result<InventoryUpdate> {
+        self.block_on(self.inner.bind_item(item_id, index))
+    }
+
+    /// Mark item templates as read in the encyclopedia.
+    pub fn read_encyclopedia(&self, schema_ids: &[&str]) -> Result<InventoryUpdate> {
+        self.block_on(self.inner.read_encyclopedia(schema_ids))
+    }
+
+    /// Send every action of `batch` in a single request, see `Tarkov::send_batch`.
+    pub fn send_batch(&self, batch: &ActionBatch) -> Result<BatchResponse> {
+        self.block_on(self.inner.send_batch(batch))
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.handle().block_on(future)
+    }
+}
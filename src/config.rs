@@ -0,0 +1,57 @@
+const GAME_VERSION: &str = "0.12.7.9018";
+const LAUNCHER_VERSION: &str = "10.2.0.1149";
+const UNITY_VERSION: &str = "2018.4.13f1";
+const BRANCH: &str = "live";
+
+const LAUNCHER_ENDPOINT: &str = "https://launcher.escapefromtarkov.com";
+const PROD_ENDPOINT: &str = "https://prod.escapefromtarkov.com";
+const TRADING_ENDPOINT: &str = "https://trading.escapefromtarkov.com";
+const RAGFAIR_ENDPOINT: &str = "https://ragfair.escapefromtarkov.com";
+
+/// Client versions and API endpoints.
+///
+/// The game updates faster than this crate, override the version strings here after a patch
+/// instead of waiting for a release.
+///
+/// ```
+/// use tarkov::config::ClientConfig;
+///
+/// let config = ClientConfig {
+///     game_version: "0.12.8.9100".to_string(),
+///     ..ClientConfig::default()
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientConfig {
+    /// Game client version.
+    pub game_version: String,
+    /// Launcher version.
+    pub launcher_version: String,
+    /// Unity engine version.
+    pub unity_version: String,
+    /// Game branch.
+    pub branch: String,
+    /// Launcher API base URL.
+    pub launcher_endpoint: String,
+    /// Main game API base URL.
+    pub prod_endpoint: String,
+    /// Trading API base URL.
+    pub trading_endpoint: String,
+    /// Flea market API base URL.
+    pub ragfair_endpoint: String,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        ClientConfig {
+            game_version: GAME_VERSION.to_string(),
+            launcher_version: LAUNCHER_VERSION.to_string(),
+            unity_version: UNITY_VERSION.to_string(),
+            branch: BRANCH.to_string(),
+            launcher_endpoint: LAUNCHER_ENDPOINT.to_string(),
+            prod_endpoint: PROD_ENDPOINT.to_string(),
+            trading_endpoint: TRADING_ENDPOINT.to_string(),
+            ragfair_endpoint: RAGFAIR_ENDPOINT.to_string(),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{handle_error, Error, ErrorResponse, Result, Tarkov, PROD_ENDPOINT};
+use crate::{handle_error, Error, ErrorResponse, Result, Tarkov};
 
 use crate::bad_json::{deserialize_integer_to_option_string, StringOrInt};
 use crate::profile::Side;
@@ -1520,6 +1520,7 @@ struct WeatherResponse {
 }
 
 #[derive(Debug, Deserialize)]
+#[allow(dead_code)]
 struct WeatherData {
     weather: Weather,
     date: String,
@@ -1685,7 +1686,7 @@ struct PricesResponse {
 impl Tarkov {
     /// Get a list of all in-game items.
     pub async fn get_items(&self) -> Result<HashMap<String, Item>> {
-        let url = format!("{}/client/items", PROD_ENDPOINT);
+        let url = format!("{}/client/items", self.config.prod_endpoint);
         let res: ItemsResponse = self.post_json(&url, &Request { crc: 0 }).await?;
 
         handle_error(res.error, res.data)
@@ -1693,7 +1694,7 @@ impl Tarkov {
 
     /// Get a list of all in-game item prices.
     pub async fn get_item_prices(&self) -> Result<HashMap<String, u64>> {
-        let url = format!("{}/client/items/prices", PROD_ENDPOINT);
+        let url = format!("{}/client/items/prices", self.config.prod_endpoint);
         let res: PricesResponse = self.post_json(&url, &Request { crc: 0 }).await?;
 
         handle_error(res.error, res.data)
@@ -1701,7 +1702,7 @@ impl Tarkov {
 
     /// Get a list of all locations/maps.
     pub async fn get_locations(&self) -> Result<Locations> {
-        let url = format!("{}/client/locations", PROD_ENDPOINT);
+        let url = format!("{}/client/locations", self.config.prod_endpoint);
         let res: LocationsResponse = self.post_json(&url, &Request { crc: 0 }).await?;
 
         handle_error(res.error, res.data)
@@ -1709,7 +1710,7 @@ impl Tarkov {
 
     /// Get the current forecast and time.
     pub async fn get_weather(&self) -> Result<Weather> {
-        let url = format!("{}/client/weather", PROD_ENDPOINT);
+        let url = format!("{}/client/weather", self.config.prod_endpoint);
         let res: WeatherResponse = self.post_json(&url, &{}).await?;
 
         handle_error(res.error, res.data).map(|w| w.weather)
@@ -1721,7 +1722,7 @@ impl Tarkov {
             return Err(Error::InvalidParameters);
         }
 
-        let url = format!("{}/client/locale/{}", PROD_ENDPOINT, language);
+        let url = format!("{}/client/locale/{}", self.config.prod_endpoint, language);
         let res: LocalizationResponse = self.post_json(&url, &{}).await?;
 
         handle_error(res.error, res.data)
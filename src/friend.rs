@@ -1,4 +1,4 @@
-use crate::{handle_error, ErrorResponse, Result, Tarkov, PROD_ENDPOINT};
+use crate::{handle_error, ErrorResponse, Result, Tarkov};
 
 use crate::profile::Side;
 use serde::{Deserialize, Serialize};
@@ -50,7 +50,7 @@ pub struct Info {
 impl Tarkov {
     /// Get a list of account's friends.
     pub async fn get_friends(&self) -> Result<Friends> {
-        let url = format!("{}/client/friend/list", PROD_ENDPOINT);
+        let url = format!("{}/client/friend/list", self.config.prod_endpoint);
         let res: FriendResponse = self.post_json(&url, &{}).await?;
 
         handle_error(res.error, res.data)
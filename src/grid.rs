@@ -0,0 +1,382 @@
+use crate::constant;
+use crate::inventory::Location;
+use crate::tree::InventoryTree;
+use std::collections::HashMap;
+
+#[cfg(test)]
+use crate::test_util::{test_db, test_tree};
+
+/// Item templates whose contents never change their size: backpacks, searchable items and
+/// simple containers.
+const CONTAINER_PARENTS: [&str; 3] = [
+    "5448e53e4bdc2d60728b4567",
+    "566168634bdc2d144c8b456c",
+    "5795f317245977243854e041",
+];
+
+/// Grid error
+#[derive(Debug, Clone, PartialEq, err_derive::Error)]
+pub enum GridError {
+    /// Item is not in the inventory.
+    #[error(display = "unknown item {}", _0)]
+    UnknownItem(String),
+    /// Item template is not in the item database.
+    #[error(display = "unknown item template {}", _0)]
+    UnknownTemplate(String),
+    /// Container does not have a grid with that name.
+    #[error(display = "no grid {} in container {}", _1, _0)]
+    UnknownGrid(String, String),
+    /// Item inside a grid has no location.
+    #[error(display = "item {} has no location", _0)]
+    MissingLocation(String),
+    /// Item overlaps another item or the grid border.
+    #[error(display = "no space for item {}", _0)]
+    NoSpace(String),
+}
+
+/// Size of a new item of `schema_id`, in cells.
+pub fn template_size(
+    db: &HashMap<String, constant::Item>,
+    schema_id: &str,
+) -> Result<(u64, u64), GridError> {
+    let props = &template(db, schema_id)?.props;
+    Ok((props.width.unwrap_or(1), props.height.unwrap_or(1)))
+}
+
+/// Size of the item `id` in cells, unrotated. Mods attached to it extend the size, a folded
+/// stock reduces it.
+pub fn item_size(
+    tree: &InventoryTree,
+    db: &HashMap<String, constant::Item>,
+    id: &str,
+) -> Result<(u64, u64), GridError> {
+    let item = tree
+        .get(id)
+        .ok_or_else(|| GridError::UnknownItem(id.to_string()))?;
+    let root = template(db, &item.schema_id)?;
+    let (mut width, height) = template_size(db, &item.schema_id)?;
+
+    let folded = is_folded(item);
+    let folded_slot = root.props.folded_slot.as_deref().unwrap_or("");
+    let foldable = root.props.foldable == Some(true);
+    if foldable && folded_slot.is_empty() && folded {
+        width = width.saturating_sub(root.props.size_reduce_right.unwrap_or(0));
+    }
+    if CONTAINER_PARENTS.contains(&root.parent.as_str()) {
+        return Ok((width, height));
+    }
+
+    // Largest extension per side, plus extensions of mods that always add up.
+    let mut extra = [0; 4];
+    let mut forced = [0; 4];
+    let mut todo = vec![id];
+    while let Some(parent_id) = todo.pop() {
+        for child in tree.children(parent_id) {
+            let slot_id = child.slot_id.as_deref().unwrap_or("");
+            if !slot_id.starts_with("mod_") {
+                continue;
+            }
+            todo.push(&child.id);
+
+            let props = &template(db, &child.schema_id)?.props;
+            if foldable && slot_id == folded_slot && (folded || is_folded(child)) {
+                continue;
+            }
+            if props.foldable == Some(true) && folded && is_folded(child) {
+                continue;
+            }
+
+            let sides = [
+                props.extra_size_left.unwrap_or(0),
+                props.extra_size_right.unwrap_or(0),
+                props.extra_size_up.unwrap_or(0),
+                props.extra_size_down.unwrap_or(0),
+            ];
+            for (i, size) in sides.iter().enumerate() {
+                if props.extra_size_force_add == Some(true) {
+                    forced[i] += size;
+                } else {
+                    extra[i] = extra[i].max(*size);
+                }
+            }
+        }
+    }
+
+    Ok((
+        width + extra[0] + extra[1] + forced[0] + forced[1],
+        height + extra[2] + extra[3] + forced[2] + forced[3],
+    ))
+}
+
+fn template<'a>(
+    db: &'a HashMap<String, constant::Item>,
+    schema_id: &str,
+) -> Result<&'a constant::Item, GridError> {
+    db.get(schema_id)
+        .ok_or_else(|| GridError::UnknownTemplate(schema_id.to_string()))
+}
+
+fn is_folded(item: &crate::inventory::Item) -> bool {
+    match item.upd.as_ref().and_then(|upd| upd.foldable.as_ref()) {
+        Some(foldable) => foldable.folded,
+        None => false,
+    }
+}
+
+fn rotate((width, height): (u64, u64), r: u64) -> (u64, u64) {
+    if r == 1 {
+        (height, width)
+    } else {
+        (width, height)
+    }
+}
+
+/// Cells of a container grid, e.g. the `hideout` grid of the stash or the `main` grid of a
+/// backpack, and the items occupying them.
+///
+/// ```no_run
+/// # async fn run(t: tarkov::Tarkov) -> tarkov::Result<()> {
+/// use tarkov::grid::ContainerGrid;
+/// use tarkov::tree::{InventoryRoot, InventoryTree};
+///
+/// let db = t.get_items().await?;
+/// let profile = t.get_profiles().await?.remove(0);
+/// let tree = InventoryTree::new(&profile.inventory);
+/// let stash = tree.root_id(InventoryRoot::Stash).unwrap();
+/// let grid = ContainerGrid::from_container(&tree, &db, stash, "hideout").unwrap();
+///
+/// // 60 rounds of 5.45x39mm BT
+/// match grid.find_purchase(&db, "56dff061d2720bb5668b4567", 60).unwrap() {
+///     Some(locations) => println!("Purchase fits at {:?}", locations),
+///     None => println!("Not enough space"),
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContainerGrid {
+    width: u64,
+    height: u64,
+    cells: Vec<Option<String>>,
+}
+
+impl ContainerGrid {
+    /// Create an empty grid.
+    pub fn new(width: u64, height: u64) -> Self {
+        ContainerGrid {
+            width,
+            height,
+            cells: vec![None; (width * height) as usize],
+        }
+    }
+
+    /// Occupancy of the `grid` grid of `container_id`.
+    pub fn from_container(
+        tree: &InventoryTree,
+        db: &HashMap<String, constant::Item>,
+        container_id: &str,
+        grid: &str,
+    ) -> Result<Self, GridError> {
+        let container = tree
+            .get(container_id)
+            .ok_or_else(|| GridError::UnknownItem(container_id.to_string()))?;
+        let props = template(db, &container.schema_id)?
+            .props
+            .grids
+            .iter()
+            .flatten()
+            .find(|g| g.name == grid)
+            .map(|g| &g.props)
+            .ok_or_else(|| GridError::UnknownGrid(container_id.to_string(), grid.to_string()))?;
+
+        // `cellsH` is the number of columns, `cellsV` the number of rows.
+        let mut cells = ContainerGrid::new(props.cells_h, props.cells_w);
+        for item in tree.children_in(container_id, grid) {
+            let location = item
+                .location
+                .as_ref()
+                .ok_or_else(|| GridError::MissingLocation(item.id.clone()))?;
+            let size = item_size(tree, db, &item.id)?;
+            cells.place(&item.id, location, size)?;
+        }
+
+        Ok(cells)
+    }
+
+    /// Number of columns.
+    pub fn width(&self) -> u64 {
+        self.width
+    }
+
+    /// Number of rows.
+    pub fn height(&self) -> u64 {
+        self.height
+    }
+
+    /// Item occupying the cell at `x`, `y`.
+    pub fn occupant(&self, x: u64, y: u64) -> Option<&str> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        self.cells[(y * self.width + x) as usize].as_deref()
+    }
+
+    /// Number of empty cells.
+    pub fn free_cells(&self) -> usize {
+        self.cells.iter().filter(|c| c.is_none()).count()
+    }
+
+    /// Returns `true` if a `width` by `height` area at `x`, `y` is inside the grid and empty.
+    pub fn is_free(&self, x: u64, y: u64, width: u64, height: u64) -> bool {
+        if x + width > self.width || y + height > self.height {
+            return false;
+        }
+
+        (y..y + height).all(|y| (x..x + width).all(|x| self.occupant(x, y).is_none()))
+    }
+
+    /// Mark the cells of an item of the unrotated `size` at `location` as occupied by `id`.
+    pub fn place(
+        &mut self,
+        id: &str,
+        location: &Location,
+        size: (u64, u64),
+    ) -> Result<(), GridError> {
+        let (width, height) = rotate(size, location.r);
+        if !self.is_free(location.x, location.y, width, height) {
+            return Err(GridError::NoSpace(id.to_string()));
+        }
+
+        for y in location.y..location.y + height {
+            for x in location.x..location.x + width {
+                self.cells[(y * self.width + x) as usize] = Some(id.to_string());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Free the cells occupied by `id`.
+    pub fn remove(&mut self, id: &str) {
+        for cell in &mut self.cells {
+            if cell.as_deref() == Some(id) {
+                *cell = None;
+            }
+        }
+    }
+
+    /// First location where an item of the unrotated `size` fits, row by row like the game.
+    /// Rotated placement is tried when the item does not fit upright.
+    pub fn find_space(&self, size: (u64, u64)) -> Option<Location> {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                for r in 0..2 {
+                    let (width, height) = rotate(size, r);
+                    if self.is_free(x, y, width, height) {
+                        return Some(Location {
+                            x,
+                            y,
+                            r,
+                            is_searched: None,
+                        });
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Locations for several new items, placed one after another. `None` if any does not fit.
+    pub fn find_spaces(&self, sizes: &[(u64, u64)]) -> Option<Vec<Location>> {
+        let mut grid = self.clone();
+        let mut locations = Vec::new();
+        for (i, size) in sizes.iter().enumerate() {
+            let location = grid.find_space(*size)?;
+            grid.place(&format!("new-{}", i), &location, *size).ok()?;
+            locations.push(location);
+        }
+
+        Some(locations)
+    }
+
+    /// Locations for the stacks of a purchase of `quantity` items of `schema_id`, or `None` if
+    /// the purchase does not fit. Check before paying for a trade or an offer.
+    pub fn find_purchase(
+        &self,
+        db: &HashMap<String, constant::Item>,
+        schema_id: &str,
+        quantity: u64,
+    ) -> Result<Option<Vec<Location>>, GridError> {
+        let stack_size = template(db, schema_id)?
+            .props
+            .stack_max_size
+            .unwrap_or(1)
+            .max(1);
+        let stacks = match quantity % stack_size {
+            0 => quantity / stack_size,
+            _ => quantity / stack_size + 1,
+        };
+        let size = template_size(db, schema_id)?;
+
+        Ok(self.find_spaces(&vec![size; stacks as usize]))
+    }
+}
+
+#[cfg(test)]
+fn rifle_tree(folded: bool) -> InventoryTree {
+    let item =
+        |id: &str, tpl: &str, parent: &str, slot: &str, location: Option<(u64, u64, u64)>| {
+            serde_json::json!({
+                "_id": id,
+                "_tpl": tpl,
+                "parentId": parent,
+                "slotId": slot,
+                "location": location.map(|(x, y, r)| serde_json::json!({ "x": x, "y": y, "r": r })),
+                "upd": { "Foldable": { "Folded": folded } },
+            })
+        };
+
+    test_tree(vec![
+        item("rifle", "rifle", "stash", "hideout", Some((0, 0, 0))),
+        item("barrel", "barrel", "rifle", "mod_barrel", None),
+        item("suppressor", "suppressor", "barrel", "mod_muzzle", None),
+        item("magazine", "magazine", "rifle", "mod_magazine", None),
+        item("ammo", "ammo", "stash", "hideout", Some((3, 0, 1))),
+    ])
+}
+
+#[test]
+fn test_item_size() {
+    let db = test_db();
+    // Only the largest of the barrel and suppressor extensions counts, the magazine always adds.
+    assert_eq!(item_size(&rifle_tree(false), &db, "rifle"), Ok((3, 2)));
+    assert_eq!(item_size(&rifle_tree(true), &db, "rifle"), Ok((2, 2)));
+    assert_eq!(
+        item_size(&rifle_tree(false), &db, "missing"),
+        Err(GridError::UnknownItem("missing".to_string()))
+    );
+}
+
+#[test]
+fn test_container_grid() {
+    let db = test_db();
+    let tree = rifle_tree(false);
+    let grid = ContainerGrid::from_container(&tree, &db, "stash", "hideout").unwrap();
+    assert_eq!((grid.width(), grid.height()), (4, 3));
+    assert_eq!(grid.occupant(2, 1), Some("rifle"));
+    assert_eq!(grid.occupant(3, 0), Some("ammo"));
+    assert_eq!(grid.free_cells(), 5);
+
+    let location = grid.find_space((1, 2)).unwrap();
+    assert_eq!((location.x, location.y, location.r), (3, 1, 0));
+    let location = grid.find_space((2, 1)).unwrap();
+    assert_eq!((location.x, location.y, location.r), (3, 1, 1));
+    assert!(grid.find_space((3, 2)).is_none());
+
+    let locations = grid.find_purchase(&db, "ammo", 300).unwrap().unwrap();
+    let cells: Vec<(u64, u64)> = locations.iter().map(|l| (l.x, l.y)).collect();
+    assert_eq!(cells, [(3, 1), (0, 2), (1, 2), (2, 2), (3, 2)]);
+    assert_eq!(grid.find_purchase(&db, "ammo", 301), Ok(None));
+}
@@ -1,34 +1,125 @@
 use rand::Rng;
 
+const PREFIX: &str = "#1-";
+const SEPARATORS: [char; 7] = [':', ':', '-', '-', '-', '-', '-'];
+const SEGMENTS: usize = 8;
+
+/// HWID validation error
+#[derive(Debug, PartialEq, err_derive::Error)]
+pub enum HwidError {
+    /// HWID does not start with `#1-`.
+    #[error(display = "missing #1- prefix")]
+    MissingPrefix,
+    /// HWID does not have 8 segments.
+    #[error(display = "expected 8 segments, found {}", _0)]
+    SegmentCount(usize),
+    /// Segments are not separated by `:` twice, then `-`.
+    #[error(display = "unexpected segment separators")]
+    Separators,
+    /// Segment is not a 32 character (24 for the last one) hex string.
+    #[error(display = "invalid segment {}", _0)]
+    InvalidSegment(usize),
+}
+
 fn random_md5<R: Rng + ?Sized>(rng: &mut R) -> String {
-    format!("{:x}", md5::compute(&rng.gen::<i32>().to_le_bytes()))
+    format!("{:x}", md5::compute(rng.gen::<i32>().to_le_bytes()))
+}
+
+fn format_hwid<F: FnMut(usize) -> String>(mut segment: F) -> String {
+    let mut hwid = PREFIX.to_string();
+    for i in 0..SEGMENTS {
+        if i > 0 {
+            hwid.push(SEPARATORS[i - 1]);
+        }
+
+        let mut hash = segment(i);
+        if i == SEGMENTS - 1 {
+            hash.truncate(hash.len() - 8);
+        }
+        hwid.push_str(&hash);
+    }
+
+    hwid
 }
 
 /// Generate a random EFT compatible HWID.
 pub fn generate_hwid() -> String {
     let mut rng = rand::thread_rng();
+    format_hwid(|_| random_md5(&mut rng))
+}
+
+/// Generate an EFT compatible HWID derived from `seed`.
+///
+/// The same seed always gives the same HWID, so a user name or a machine fingerprint can be used
+/// to keep a stable identity without storing the HWID.
+pub fn generate_hwid_from_seed<S: AsRef<[u8]>>(seed: S) -> String {
+    format_hwid(|i| {
+        let mut data = seed.as_ref().to_vec();
+        data.push(i as u8);
+        format!("{:x}", md5::compute(data))
+    })
+}
 
-    let short_md5 = {
-        let mut hash = random_md5(&mut rng);
-        hash.truncate(hash.len() - 8);
+/// Split a HWID into its 8 hex segments, checking the `#1-` format.
+pub fn parse_hwid(hwid: &str) -> Result<Vec<&str>, HwidError> {
+    if !hwid.starts_with(PREFIX) {
+        return Err(HwidError::MissingPrefix);
+    }
 
-        hash
-    };
+    let hwid = &hwid[PREFIX.len()..];
+    let segments: Vec<&str> = hwid.split(&[':', '-'][..]).collect();
+    if segments.len() != SEGMENTS {
+        return Err(HwidError::SegmentCount(segments.len()));
+    }
 
-    format!(
-        "#1-{}:{}:{}-{}-{}-{}-{}-{}",
-        random_md5(&mut rng),
-        random_md5(&mut rng),
-        random_md5(&mut rng),
-        random_md5(&mut rng),
-        random_md5(&mut rng),
-        random_md5(&mut rng),
-        random_md5(&mut rng),
-        short_md5
-    )
+    let separators = hwid.chars().filter(|c| SEPARATORS.contains(c));
+    if !separators.eq(SEPARATORS.iter().copied()) {
+        return Err(HwidError::Separators);
+    }
+
+    for (i, segment) in segments.iter().enumerate() {
+        let len = if i == SEGMENTS - 1 { 24 } else { 32 };
+        if segment.len() != len || !segment.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(HwidError::InvalidSegment(i));
+        }
+    }
+
+    Ok(segments)
+}
+
+/// Returns `true` if `hwid` has the `#1-` format used by the EFT launcher.
+pub fn is_valid_hwid(hwid: &str) -> bool {
+    parse_hwid(hwid).is_ok()
 }
 
 #[test]
 fn test_generate_hwid() {
-    assert_eq!(generate_hwid().len(), 258)
+    assert_eq!(generate_hwid().len(), 258);
+    assert!(is_valid_hwid(&generate_hwid()));
+}
+
+#[test]
+fn test_generate_hwid_from_seed() {
+    let hwid = generate_hwid_from_seed("me@example.com");
+    assert_eq!(hwid, generate_hwid_from_seed("me@example.com"));
+    assert_ne!(hwid, generate_hwid_from_seed("you@example.com"));
+    assert_eq!(parse_hwid(&hwid).unwrap().len(), 8);
+}
+
+#[test]
+fn test_parse_hwid() {
+    let hwid = generate_hwid();
+    assert_eq!(parse_hwid(&hwid[1..]), Err(HwidError::MissingPrefix));
+    assert_eq!(
+        parse_hwid(&hwid[..hwid.len() - 25]),
+        Err(HwidError::SegmentCount(7))
+    );
+    assert_eq!(
+        parse_hwid(&hwid.replace(':', "-")),
+        Err(HwidError::Separators)
+    );
+    assert_eq!(
+        parse_hwid(&hwid[..hwid.len() - 1]),
+        Err(HwidError::InvalidSegment(7))
+    );
 }
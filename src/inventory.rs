@@ -1,6 +1,8 @@
 use crate::bad_json::deserialize_bad_location_as_none;
-use crate::ErrorResponse;
+use crate::batch::ActionBatch;
+use crate::{handle_error2, Error, ErrorResponse, Result, Tarkov};
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 #[derive(Debug, Serialize)]
 pub(crate) struct MoveItemRequest<'a, T> {
@@ -8,12 +10,106 @@ pub(crate) struct MoveItemRequest<'a, T> {
     pub(crate) tm: u64,
 }
 
+#[derive(Debug, Deserialize)]
+pub(crate) struct MoveItemResponse {
+    #[serde(flatten)]
+    pub(crate) error: ErrorResponse,
+    pub(crate) data: serde_json::Value,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct RagfairResponseData {
     pub(crate) items: serde_json::Value,
     #[serde(rename = "badRequest")]
-    pub(crate) errors: Vec<ErrorResponse>,
+    pub(crate) errors: Vec<BadRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct BadRequest {
+    index: Option<usize>,
+    #[serde(flatten)]
+    error: ErrorResponse,
+}
+
+/// Inventory error
+#[derive(Debug, err_derive::Error)]
+pub enum InventoryError {
+    /// Not enough space in the stash.
+    #[error(display = "not enough space in stash")]
+    NotEnoughSpace,
+    /// Item could not be examined.
+    #[error(display = "examination failed")]
+    ExaminationFailed,
+    /// Item was already examined.
+    #[error(display = "item is already examined")]
+    AlreadyExamined,
+}
+
+impl InventoryError {
+    /// Map a backend error code of the inventory API.
+    pub(crate) fn from_code(code: u64) -> Option<Self> {
+        match code {
+            223 => Some(InventoryError::NotEnoughSpace),
+            22001 => Some(InventoryError::ExaminationFailed),
+            22002 => Some(InventoryError::AlreadyExamined),
+            _ => None,
+        }
+    }
+}
+
+/// Action of an item moving request rejected by the API.
+#[derive(Debug)]
+pub struct ActionError {
+    /// Index of the action in the request, `None` if the API did not send it.
+    pub index: Option<usize>,
+    /// Reason the action was rejected.
+    pub error: Error,
+}
+
+/// Every action of an item moving request rejected by the API.
+#[derive(Debug)]
+pub struct ActionErrors(pub Vec<ActionError>);
+
+impl fmt::Display for ActionErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, e) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            match e.index {
+                Some(index) => write!(f, "action {}: {}", index, e.error)?,
+                None => write!(f, "unknown action: {}", e.error)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl RagfairResponseData {
+    /// Map the `badRequest` entries.
+    pub(crate) fn action_errors(&mut self) -> Vec<ActionError> {
+        self.errors
+            .drain(..)
+            .filter_map(|BadRequest { index, error }| {
+                handle_error2(error)
+                    .err()
+                    .map(|error| ActionError { index, error })
+            })
+            .collect()
+    }
+
+    /// Map the `badRequest` entries. A single entry is returned as its own error, several as
+    /// `Error::ActionErrors`.
+    pub(crate) fn handle_errors(&mut self) -> Result<()> {
+        let mut errors = self.action_errors();
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.remove(0).error),
+            _ => Err(Error::ActionErrors(ActionErrors(errors))),
+        }
+    }
 }
 
 /// Changes to the player's inventory after interacting with traders or the flea market.
@@ -96,6 +192,8 @@ pub struct Upd {
     pub key: Option<UpdKey>,
     /// Tag info
     pub tag: Option<UpdTag>,
+    /// Foldable stock info
+    pub foldable: Option<UpdFoldable>,
 }
 
 /// Medkit item info
@@ -144,6 +242,117 @@ pub struct UpdTag {
     pub name: String,
 }
 
+/// Foldable stock info
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct UpdFoldable {
+    /// Stock is folded
+    pub folded: bool,
+}
+
+/// Equipment slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EquipmentSlot {
+    /// Primary weapon
+    FirstPrimaryWeapon,
+    /// Secondary weapon
+    SecondPrimaryWeapon,
+    /// Pistol
+    Holster,
+    /// Melee weapon
+    Scabbard,
+    /// Face cover
+    FaceCover,
+    /// Helmet or hat
+    Headwear,
+    /// Headset
+    Earpiece,
+    /// Tactical rig
+    TacticalVest,
+    /// Body armor
+    ArmorVest,
+    /// Glasses
+    Eyewear,
+    /// Backpack
+    Backpack,
+    /// Secure container
+    SecuredContainer,
+    /// Armband
+    ArmBand,
+    /// Pockets
+    Pockets,
+}
+
+impl EquipmentSlot {
+    /// Slot ID used by the API.
+    pub fn slot_id(self) -> &'static str {
+        match self {
+            EquipmentSlot::FirstPrimaryWeapon => "FirstPrimaryWeapon",
+            EquipmentSlot::SecondPrimaryWeapon => "SecondPrimaryWeapon",
+            EquipmentSlot::Holster => "Holster",
+            EquipmentSlot::Scabbard => "Scabbard",
+            EquipmentSlot::FaceCover => "FaceCover",
+            EquipmentSlot::Headwear => "Headwear",
+            EquipmentSlot::Earpiece => "Earpiece",
+            EquipmentSlot::TacticalVest => "TacticalVest",
+            EquipmentSlot::ArmorVest => "ArmorVest",
+            EquipmentSlot::Eyewear => "Eyewear",
+            EquipmentSlot::Backpack => "Backpack",
+            EquipmentSlot::SecuredContainer => "SecuredContainer",
+            EquipmentSlot::ArmBand => "ArmBand",
+            EquipmentSlot::Pockets => "Pockets",
+        }
+    }
+}
+
+/// Slot or grid an item is put into.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Container {
+    /// Parent item ID
+    pub id: String,
+    /// Slot or grid ID of the parent, e.g. `hideout` for the stash, `main` for a backpack or
+    /// `mod_scope` for a weapon.
+    #[serde(rename = "container")]
+    pub slot_id: String,
+    /// Location inside a grid
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+}
+
+impl Container {
+    /// Location inside the `grid_id` grid of `parent_id`.
+    pub fn grid(parent_id: &str, grid_id: &str, location: Location) -> Self {
+        Container {
+            id: parent_id.to_string(),
+            slot_id: grid_id.to_string(),
+            location: Some(location),
+        }
+    }
+
+    /// Equipment slot. `equipment_id` is `Inventory::equipment`.
+    pub fn equipment(equipment_id: &str, slot: EquipmentSlot) -> Self {
+        Container {
+            id: equipment_id.to_string(),
+            slot_id: slot.slot_id().to_string(),
+            location: None,
+        }
+    }
+
+    /// Mod slot of `parent_id`, e.g. `mod_scope`, or `patron_in_weapon` for the chambered
+    /// round. Other IDs fail with `Error::InvalidParameters`, use `grid` or `equipment` instead.
+    pub fn slot(parent_id: &str, slot_id: &str) -> Result<Self> {
+        if parent_id.is_empty() || !(slot_id.starts_with("mod_") || slot_id == "patron_in_weapon") {
+            return Err(Error::InvalidParameters);
+        }
+
+        Ok(Container {
+            id: parent_id.to_string(),
+            slot_id: slot_id.to_string(),
+            location: None,
+        })
+    }
+}
+
 /// Inventory item for trading.
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct BarterItem {
@@ -152,3 +361,136 @@ pub struct BarterItem {
     /// Amount of items.
     pub count: f64,
 }
+
+impl Tarkov {
+    /// Move an item into a slot or grid.
+    pub async fn move_item(&self, item_id: &str, to: &Container) -> Result<InventoryUpdate> {
+        if item_id.is_empty() || to.id.is_empty() || to.slot_id.is_empty() {
+            return Err(Error::InvalidParameters);
+        }
+
+        let mut batch = ActionBatch::new();
+        batch.move_item(item_id, to);
+        self.send_single(batch).await
+    }
+
+    /// Split `count` items off a stack into a new stack at `to`.
+    pub async fn split_item(
+        &self,
+        item_id: &str,
+        to: &Container,
+        count: u64,
+    ) -> Result<InventoryUpdate> {
+        if item_id.is_empty() || to.id.is_empty() || to.slot_id.is_empty() || count == 0 {
+            return Err(Error::InvalidParameters);
+        }
+
+        let mut batch = ActionBatch::new();
+        batch.split(item_id, to, count);
+        self.send_single(batch).await
+    }
+
+    /// Merge the stack of `item_id` into the stack of `with_id`.
+    pub async fn merge_item(&self, item_id: &str, with_id: &str) -> Result<InventoryUpdate> {
+        if item_id.is_empty() || with_id.is_empty() {
+            return Err(Error::InvalidParameters);
+        }
+
+        let mut batch = ActionBatch::new();
+        batch.merge(item_id, with_id);
+        self.send_single(batch).await
+    }
+
+    /// Move `count` items from the stack of `item_id` to the stack of `with_id`.
+    pub async fn transfer_item(
+        &self,
+        item_id: &str,
+        with_id: &str,
+        count: u64,
+    ) -> Result<InventoryUpdate> {
+        if item_id.is_empty() || with_id.is_empty() || count == 0 {
+            return Err(Error::InvalidParameters);
+        }
+
+        let mut batch = ActionBatch::new();
+        batch.transfer(item_id, with_id, count);
+        self.send_single(batch).await
+    }
+
+    /// Fold or unfold the stock of a weapon.
+    pub async fn fold_item(&self, item_id: &str, folded: bool) -> Result<InventoryUpdate> {
+        if item_id.is_empty() {
+            return Err(Error::InvalidParameters);
+        }
+
+        let mut batch = ActionBatch::new();
+        batch.fold(item_id, folded);
+        self.send_single(batch).await
+    }
+
+    /// Turn a device, such as a flashlight or night vision, on or off.
+    pub async fn toggle_item(&self, item_id: &str, on: bool) -> Result<InventoryUpdate> {
+        if item_id.is_empty() {
+            return Err(Error::InvalidParameters);
+        }
+
+        let mut batch = ActionBatch::new();
+        batch.toggle(item_id, on);
+        self.send_single(batch).await
+    }
+
+    /// Examine an unknown item.
+    pub async fn examine_item(&self, item_id: &str) -> Result<InventoryUpdate> {
+        if item_id.is_empty() {
+            return Err(Error::InvalidParameters);
+        }
+
+        let mut batch = ActionBatch::new();
+        batch.examine(item_id);
+        self.send_single(batch).await
+    }
+
+    /// Discard an item and everything inside it.
+    pub async fn remove_item(&self, item_id: &str) -> Result<InventoryUpdate> {
+        if item_id.is_empty() {
+            return Err(Error::InvalidParameters);
+        }
+
+        let mut batch = ActionBatch::new();
+        batch.remove(item_id);
+        self.send_single(batch).await
+    }
+
+    /// Name and color a container.
+    pub async fn tag_item(&self, item_id: &str, name: &str, color: u64) -> Result<InventoryUpdate> {
+        if item_id.is_empty() {
+            return Err(Error::InvalidParameters);
+        }
+
+        let mut batch = ActionBatch::new();
+        batch.tag(item_id, name, color);
+        self.send_single(batch).await
+    }
+
+    /// Bind an item to the quick access slot `index`.
+    pub async fn bind_item(&self, item_id: &str, index: u64) -> Result<InventoryUpdate> {
+        if item_id.is_empty() {
+            return Err(Error::InvalidParameters);
+        }
+
+        let mut batch = ActionBatch::new();
+        batch.bind(item_id, index);
+        self.send_single(batch).await
+    }
+
+    /// Mark item templates as read in the encyclopedia.
+    pub async fn read_encyclopedia(&self, schema_ids: &[&str]) -> Result<InventoryUpdate> {
+        if schema_ids.is_empty() {
+            return Err(Error::InvalidParameters);
+        }
+
+        let mut batch = ActionBatch::new();
+        batch.read_encyclopedia(schema_ids);
+        self.send_single(batch).await
+    }
+}
@@ -10,55 +10,87 @@
 //! For examples, see the `examples` directory in the source tree.
 
 #![warn(missing_docs)]
+// `err-derive` generates its impls inside anonymous constants.
+#![allow(non_local_definitions)]
 
 use crate::auth::LoginError;
-use crate::hwid::generate_hwid;
+use crate::config::ClientConfig;
+use crate::hwid::{generate_hwid, parse_hwid, HwidError};
+use crate::inventory::{ActionErrors, InventoryError};
+use crate::login::LoginState;
 use crate::profile::ProfileError;
 use crate::ragfair::RagfairError;
+use crate::rate_limit::{RateLimited, RateLimiter};
+use crate::record::Recorder;
+use crate::retry::RetryPolicy;
+use crate::session::SessionState;
 use crate::trading::TradingError;
+use crate::transport::{HyperTransport, Transport};
 use err_derive::Error;
 use flate2::read::ZlibDecoder;
-use hyper::body::Buf;
-use hyper::client::connect::dns::GaiResolver;
-use hyper::client::{Client, HttpConnector};
-use hyper::Body;
 use hyper::Request;
 use hyper::{Method, StatusCode};
-use hyper_tls::HttpsConnector;
-use log::debug;
+use log::{debug, warn};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::io::Read;
-
-const GAME_VERSION: &str = "0.12.7.9018";
-const LAUNCHER_VERSION: &str = "10.2.0.1149";
-const UNITY_VERSION: &str = "2018.4.13f1";
-
-const LAUNCHER_ENDPOINT: &str = "https://launcher.escapefromtarkov.com";
-const PROD_ENDPOINT: &str = "https://prod.escapefromtarkov.com";
-const TRADING_ENDPOINT: &str = "https://trading.escapefromtarkov.com";
-const RAGFAIR_ENDPOINT: &str = "https://ragfair.escapefromtarkov.com";
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{oneshot, Mutex as AsyncMutex};
+use tokio::time::Instant;
 
 mod bad_json;
+#[cfg(test)]
+mod test_util;
 
 /// Structs for authentication.
 pub mod auth;
+/// Batched item moving actions.
+pub mod batch;
+/// Blocking client.
+#[cfg(feature = "blocking")]
+pub mod blocking;
+/// Client versions and API endpoints.
+pub mod config;
 /// Structs for game constants API.
 pub mod constant;
 /// Structs for the Friend API.
 pub mod friend;
+/// Container grid occupancy.
+pub mod grid;
 /// Helper functions for hardware ID.
 pub mod hwid;
 /// Structs for inventory and items.
 pub mod inventory;
+/// Step-by-step login.
+pub mod login;
 /// Flea market search helpers.
 pub mod market_filter;
+/// Offline mock EFT backend.
+#[cfg(feature = "mock")]
+pub mod mock;
+/// Stash maintenance planner.
+pub mod planner;
+/// Multi-account client pool.
+pub mod pool;
 /// Structs for the Profile API.
 pub mod profile;
 /// Structs for the Flea Market (Ragfair) API.
 pub mod ragfair;
+/// Client-side rate limiting.
+pub mod rate_limit;
+/// Record and replay of API traffic.
+pub mod record;
+/// Retry policy for transient failures.
+pub mod retry;
+/// Persistent session state.
+pub mod session;
 /// Structs for the Trading API.
 pub mod trading;
+/// HTTP transport abstraction.
+pub mod transport;
+/// Inventory item hierarchy.
+pub mod tree;
 
 /// Common error enum returned by most functions.
 #[derive(Debug, Error)]
@@ -78,34 +110,131 @@ pub enum Error {
     /// Generic non-success response from the API.
     #[error(display = "non-success response from api: {}", _0)]
     Status(StatusCode),
-    /// Invalid or missing parameters.
+    /// Invalid or missing parameters, rejected before sending the request.
     #[error(display = "invalid or missing login parameters")]
     InvalidParameters,
+    /// API rejected the request parameters.
+    #[error(display = "invalid request parameters{}", _0)]
+    InvalidRequest(ErrorMessage),
+    /// No recorded response left for the request URL.
+    #[error(display = "no recorded response for {}", _0)]
+    Replay(String),
+    /// API returned no error, but no data either.
+    #[error(display = "api returned no errors but data is unavailable")]
+    MissingData,
+    /// Trader assort item without a loyalty level. Contains the trader and item IDs.
+    #[error(display = "no loyalty level for item {} of trader {}", _1, _0)]
+    MissingLoyaltyLevel(String, String),
+    /// Trader assort item without a price or barter scheme. Contains the trader and item IDs.
+    #[error(display = "no price for item {} of trader {}", _1, _0)]
+    MissingPrice(String, String),
 
     /// Unidentified error within the EFT API.
-    #[error(display = "unidentified login error with error code: {}", _0)]
-    UnknownAPIError(u64),
+    #[error(display = "unidentified api error with error code: {}{}", _0, _1)]
+    UnknownAPIError(u64, ErrorMessage),
     /// Not authorized to API or profile is not selected.
-    #[error(display = "not authorized or game profile not selected")]
-    NotAuthorized,
+    #[error(display = "not authorized or game profile not selected{}", _0)]
+    NotAuthorized(ErrorMessage),
     /// EFT API is down for maintenance.
-    #[error(display = "api is down for maintenance")]
-    Maintenance,
+    #[error(display = "api is down for maintenance{}", _0)]
+    Maintenance(ErrorMessage),
     /// Backend error. No other information is given.
-    #[error(display = "backend error")]
-    BackendError,
+    #[error(display = "backend error{}", _0)]
+    BackendError(ErrorMessage),
     /// Authentication API error.
-    #[error(display = "login api error: {}", _0)]
-    LoginError(#[error(source)] LoginError),
+    #[error(display = "login api error: {}{}", _0, _1)]
+    LoginError(#[error(source)] LoginError, ErrorMessage),
     /// Profile API error.
-    #[error(display = "profile api error: {}", _0)]
-    ProfileError(#[error(source)] ProfileError),
+    #[error(display = "profile api error: {}{}", _0, _1)]
+    ProfileError(#[error(source)] ProfileError, ErrorMessage),
     /// Trading API error.
-    #[error(display = "trading api error: {}", _0)]
-    TradingError(#[error(source)] TradingError),
+    #[error(display = "trading api error: {}{}", _0, _1)]
+    TradingError(#[error(source)] TradingError, ErrorMessage),
     /// Ragfair API error.
-    #[error(display = "trading api error: {}", _0)]
-    RagfairError(#[error(source)] RagfairError),
+    #[error(display = "ragfair api error: {}{}", _0, _1)]
+    RagfairError(#[error(source)] RagfairError, ErrorMessage),
+    /// Inventory API error.
+    #[error(display = "inventory api error: {}{}", _0, _1)]
+    InventoryError(#[error(source)] InventoryError, ErrorMessage),
+    /// Several actions of an item moving request were rejected.
+    #[error(display = "item moving actions failed: {}", _0)]
+    ActionErrors(ActionErrors),
+    /// Account is not in the `TarkovPool`.
+    #[error(display = "unknown account: {}", _0)]
+    UnknownAccount(String),
+    /// `TarkovPool` has no accounts.
+    #[error(display = "pool has no accounts")]
+    EmptyPool,
+    /// `LoginFlow` step does not match its current state, which is contained.
+    #[error(display = "unexpected login step in state {:?}", _0)]
+    UnexpectedLoginStep(LoginState),
+    /// Malformed hardware ID.
+    #[error(display = "invalid hwid: {}", _0)]
+    HwidError(#[error(source)] HwidError),
+}
+
+impl Error {
+    /// Original error message (`errmsg`) sent by the API, if any.
+    pub fn message(&self) -> Option<&str> {
+        match self {
+            Error::UnknownAPIError(_, m)
+            | Error::InvalidRequest(m)
+            | Error::NotAuthorized(m)
+            | Error::Maintenance(m)
+            | Error::BackendError(m)
+            | Error::LoginError(_, m)
+            | Error::ProfileError(_, m)
+            | Error::TradingError(_, m)
+            | Error::RagfairError(_, m)
+            | Error::InventoryError(_, m) => m.0.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+impl From<LoginError> for Error {
+    fn from(e: LoginError) -> Self {
+        Error::LoginError(e, ErrorMessage::default())
+    }
+}
+
+impl From<ProfileError> for Error {
+    fn from(e: ProfileError) -> Self {
+        Error::ProfileError(e, ErrorMessage::default())
+    }
+}
+
+impl From<TradingError> for Error {
+    fn from(e: TradingError) -> Self {
+        Error::TradingError(e, ErrorMessage::default())
+    }
+}
+
+impl From<RagfairError> for Error {
+    fn from(e: RagfairError) -> Self {
+        Error::RagfairError(e, ErrorMessage::default())
+    }
+}
+
+impl From<InventoryError> for Error {
+    fn from(e: InventoryError) -> Self {
+        Error::InventoryError(e, ErrorMessage::default())
+    }
+}
+
+/// Error message (`errmsg`) sent by the API alongside an error code.
+///
+/// Displayed as `: message`, or nothing if the API did not send one.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ErrorMessage(pub Option<String>);
+
+impl std::fmt::Display for ErrorMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.0 {
+            Some(message) if !message.is_empty() => write!(f, ": {}", message),
+            _ => Ok(()),
+        }
+    }
 }
 
 /// `Result` alias type.
@@ -121,110 +250,322 @@ struct ErrorResponse {
 
 /// Client for the EFT API.
 pub struct Tarkov {
-    client: Client<HttpsConnector<HttpConnector<GaiResolver>>, Body>,
-    /// Hardware ID
-    pub hwid: String,
-    /// Session cookie
-    pub session: String,
+    transport: Arc<dyn Transport>,
+    /// Configuration at build time, the current game version is in `game_version`.
+    config: ClientConfig,
+    game_version: Arc<Mutex<String>>,
+    retry: RetryPolicy,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    last_request: Arc<Mutex<Instant>>,
+    keep_alive: Option<oneshot::Sender<()>>,
+    state: Arc<Mutex<SessionState>>,
+    renewing: Arc<AsyncMutex<()>>,
+    renewals: Arc<AtomicU64>,
+    sequence: Arc<AtomicU64>,
+    moving: Arc<AsyncMutex<()>>,
+    auto_renew: bool,
 }
 
-impl Tarkov {
+/// Builder for a `Tarkov` client with a custom transport.
+///
+/// ```no_run
+/// # async fn run() -> tarkov::Result<()> {
+/// use tarkov::transport::HyperTransport;
+/// use tarkov::Tarkov;
+///
+/// let t = Tarkov::builder()
+///     .transport(HyperTransport::new())
+///     .from_session("e1bc65a216325f0ad0db8518fa299db2");
+/// # Ok(())
+/// # }
+/// ```
+pub struct TarkovBuilder {
+    transport: Arc<dyn Transport>,
+    config: ClientConfig,
+    retry: RetryPolicy,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    recorder: Option<Box<dyn Write + Send>>,
+}
+
+impl Default for TarkovBuilder {
+    fn default() -> Self {
+        TarkovBuilder {
+            transport: Arc::new(HyperTransport::new()),
+            config: ClientConfig::default(),
+            retry: RetryPolicy::none(),
+            rate_limiter: None,
+            recorder: None,
+        }
+    }
+}
+
+impl TarkovBuilder {
+    /// Use a custom HTTP transport.
+    pub fn transport<T: Transport + 'static>(mut self, transport: T) -> Self {
+        self.transport = Arc::new(transport);
+        self
+    }
+
+    /// Override the client versions, branch and API endpoints.
+    pub fn config(mut self, config: ClientConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Retry transient failures of authenticated requests. Disabled by default.
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Throttle every request, including login, with a per-host rate limiter.
+    pub fn rate_limit(mut self, limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(Arc::new(limiter));
+        self
+    }
+
+    /// Write every request and response to `sink` as JSONL, see `record::Record`.
+    pub fn record<W: Write + Send + 'static>(mut self, sink: W) -> Self {
+        self.recorder = Some(Box::new(sink));
+        self
+    }
+
     /// Login with email and password.
-    pub async fn login(email: &str, password: &str, hwid: &str) -> Result<Self> {
+    pub async fn login(mut self, email: &str, password: &str, hwid: &str) -> Result<Tarkov> {
         if email.is_empty() || password.is_empty() || hwid.is_empty() {
             return Err(Error::InvalidParameters);
         }
+        parse_hwid(hwid)?;
+        self.wrap_transport();
 
-        let https = HttpsConnector::new();
-        let client = Client::builder().build::<_, Body>(https);
-
-        let user = auth::login(&client, email, password, None, &hwid).await?;
-        let session = auth::exchange_access_token(&client, &user.access_token, &hwid).await?;
-
-        Ok(Tarkov {
-            client,
-            hwid: hwid.to_string(),
-            session: session.session,
-        })
+        let user = auth::login(&*self.transport, &self.config, email, password, None, hwid).await?;
+        let state = self.login_session(user, hwid).await?;
+        Ok(self.build(state))
     }
 
     /// Login with email, password and captcha.
     pub async fn login_with_captcha(
+        mut self,
         email: &str,
         password: &str,
         captcha: &str,
         hwid: &str,
-    ) -> Result<Self> {
+    ) -> Result<Tarkov> {
         if email.is_empty() || password.is_empty() || captcha.is_empty() || hwid.is_empty() {
             return Err(Error::InvalidParameters);
         }
-
-        let https = HttpsConnector::new();
-        let client = Client::builder().build::<_, Body>(https);
-
-        let user = auth::login(&client, email, password, Some(captcha), &hwid).await?;
-        let session = auth::exchange_access_token(&client, &user.access_token, &hwid).await?;
-
-        Ok(Tarkov {
-            client,
-            hwid: hwid.to_string(),
-            session: session.session,
-        })
+        parse_hwid(hwid)?;
+        self.wrap_transport();
+
+        let user = auth::login(
+            &*self.transport,
+            &self.config,
+            email,
+            password,
+            Some(captcha),
+            hwid,
+        )
+        .await?;
+        let state = self.login_session(user, hwid).await?;
+        Ok(self.build(state))
     }
 
     /// Login with email, password and 2FA code.
     pub async fn login_with_2fa(
+        mut self,
         email: &str,
         password: &str,
         code: &str,
         hwid: &str,
-    ) -> Result<Self> {
+    ) -> Result<Tarkov> {
         if email.is_empty() || password.is_empty() || code.is_empty() || hwid.is_empty() {
             return Err(Error::InvalidParameters);
         }
+        parse_hwid(hwid)?;
+        self.wrap_transport();
 
-        let https = HttpsConnector::new();
-        let client = Client::builder().build::<_, Body>(https);
-
-        let _ = auth::activate_hardware(&client, email, code, &hwid).await?;
-        let user = auth::login(&client, email, password, None, &hwid).await?;
-        let session = auth::exchange_access_token(&client, &user.access_token, &hwid).await?;
-
-        Ok(Tarkov {
-            client,
-            hwid: hwid.to_string(),
-            session: session.session,
-        })
+        auth::activate_hardware(&*self.transport, &self.config, email, code, hwid).await?;
+        let user = auth::login(&*self.transport, &self.config, email, password, None, hwid).await?;
+        let state = self.login_session(user, hwid).await?;
+        Ok(self.build(state))
     }
 
     /// Login with a Bearer token.
-    pub async fn from_access_token(access_token: &str, hwid: &str) -> Result<Self> {
+    pub async fn from_access_token(mut self, access_token: &str, hwid: &str) -> Result<Tarkov> {
         if access_token.is_empty() || hwid.is_empty() {
             return Err(Error::InvalidParameters);
         }
+        parse_hwid(hwid)?;
+        self.wrap_transport();
 
-        let https = HttpsConnector::new();
-        let client = Client::builder().build::<_, Body>(https);
-        let session = auth::exchange_access_token(&client, &access_token, &hwid).await?;
+        let mut session = self.exchange_access_token(access_token, hwid, None).await?;
+        if session.queued {
+            session =
+                auth::wait_in_queue(&*self.transport, &mut self.config, access_token, hwid).await?;
+        }
 
-        Ok(Tarkov {
-            client,
-            hwid: hwid.to_string(),
+        Ok(self.build(SessionState {
             session: session.session,
-        })
+            hwid: hwid.to_string(),
+            access_token: Some(access_token.to_string()),
+            ..SessionState::default()
+        }))
     }
 
     /// Login with a cookie session (AKA `PHPSESSID`).
-    pub fn from_session(session: &str) -> Self {
-        let https = HttpsConnector::new();
-        let client = Client::builder().build::<_, Body>(https);
+    pub fn from_session(mut self, session: &str) -> Tarkov {
+        self.wrap_transport();
+        self.build(SessionState {
+            session: session.to_string(),
+            hwid: generate_hwid(),
+            ..SessionState::default()
+        })
+    }
+
+    /// Exchange the access token of a launcher login for a session, waiting in the login queue
+    /// if needed.
+    async fn login_session(&mut self, user: auth::Auth, hwid: &str) -> Result<SessionState> {
+        let access_token = user.access_token.clone();
+        let (session, mut state) = self.start_session(user, hwid).await?;
+        if session.queued {
+            state.session =
+                auth::wait_in_queue(&*self.transport, &mut self.config, &access_token, hwid)
+                    .await?
+                    .session;
+        }
+
+        Ok(state)
+    }
+
+    /// Exchange the access token of a launcher login for a session.
+    async fn start_session(
+        &mut self,
+        user: auth::Auth,
+        hwid: &str,
+    ) -> Result<(auth::Session, SessionState)> {
+        let session = self
+            .exchange_access_token(&user.access_token, hwid, user.game_version.as_deref())
+            .await?;
+
+        let mut state = SessionState {
+            session: session.session.clone(),
+            hwid: hwid.to_string(),
+            ..SessionState::default()
+        };
+        state.set_tokens(user.access_token, user.refresh_token, user.expires_in);
+        Ok((session, state))
+    }
 
+    /// Exchange an access token for a session, see `auth::start_game`.
+    async fn exchange_access_token(
+        &mut self,
+        access_token: &str,
+        hwid: &str,
+        game_version: Option<&str>,
+    ) -> Result<auth::Session> {
+        auth::start_game(
+            &*self.transport,
+            &mut self.config,
+            access_token,
+            hwid,
+            game_version,
+        )
+        .await
+    }
+
+    /// Wrap the transport with the configured recorder and rate limiter. Called once by every
+    /// method that consumes the builder, so the order of the builder calls does not matter.
+    fn wrap_transport(&mut self) {
+        if let Some(sink) = self.recorder.take() {
+            self.transport = Arc::new(Recorder::new(self.transport.clone(), sink));
+        }
+        if let Some(limiter) = &self.rate_limiter {
+            self.transport = Arc::new(RateLimited {
+                inner: self.transport.clone(),
+                limiter: limiter.clone(),
+            });
+        }
+    }
+
+    fn build(&self, state: SessionState) -> Tarkov {
         Tarkov {
-            client,
-            hwid: generate_hwid(),
-            session: session.to_string(),
+            transport: self.transport.clone(),
+            config: self.config.clone(),
+            game_version: Arc::new(Mutex::new(self.config.game_version.clone())),
+            retry: self.retry.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            last_request: Arc::new(Mutex::new(Instant::now())),
+            keep_alive: None,
+            state: Arc::new(Mutex::new(state)),
+            renewing: Arc::new(AsyncMutex::new(())),
+            renewals: Arc::new(AtomicU64::new(0)),
+            sequence: Arc::new(AtomicU64::new(0)),
+            moving: Arc::new(AsyncMutex::new(())),
+            auto_renew: true,
         }
     }
+}
+
+impl Tarkov {
+    /// Create a builder to configure the client before authenticating.
+    pub fn builder() -> TarkovBuilder {
+        TarkovBuilder::default()
+    }
+
+    /// Client versions and API endpoints in use. The game version follows newer versions found
+    /// while renewing the session.
+    pub fn config(&self) -> ClientConfig {
+        ClientConfig {
+            game_version: self.game_version.lock().unwrap().clone(),
+            ..self.config.clone()
+        }
+    }
+
+    /// Rate limiter in use, if any.
+    pub fn rate_limiter(&self) -> Option<&RateLimiter> {
+        self.rate_limiter.as_deref()
+    }
+
+    /// Login with email and password.
+    pub async fn login(email: &str, password: &str, hwid: &str) -> Result<Self> {
+        Tarkov::builder().login(email, password, hwid).await
+    }
+
+    /// Login with email, password and captcha.
+    pub async fn login_with_captcha(
+        email: &str,
+        password: &str,
+        captcha: &str,
+        hwid: &str,
+    ) -> Result<Self> {
+        Tarkov::builder()
+            .login_with_captcha(email, password, captcha, hwid)
+            .await
+    }
+
+    /// Login with email, password and 2FA code.
+    pub async fn login_with_2fa(
+        email: &str,
+        password: &str,
+        code: &str,
+        hwid: &str,
+    ) -> Result<Self> {
+        Tarkov::builder()
+            .login_with_2fa(email, password, code, hwid)
+            .await
+    }
+
+    /// Login with a Bearer token.
+    pub async fn from_access_token(access_token: &str, hwid: &str) -> Result<Self> {
+        Tarkov::builder()
+            .from_access_token(access_token, hwid)
+            .await
+    }
+
+    /// Login with a cookie session (AKA `PHPSESSID`).
+    pub fn from_session(session: &str) -> Self {
+        Tarkov::builder().from_session(session)
+    }
 
     async fn post_json<S, T>(&self, url: &str, body: &S) -> Result<T>
     where
@@ -233,14 +574,76 @@ impl Tarkov {
     {
         debug!("Sending request to {} ({:?})", url, body);
         let body = match serde_json::to_string(&body) {
-            Ok(body) => Ok(Body::from(if body == "null" {
+            Ok(body) => Ok(if body == "null" {
                 "{}".to_string()
             } else {
                 body
-            })),
+            }),
             Err(e) => Err(e),
         }?;
 
+        // Item moving actions (buying, selling, trading) are not safe to send twice.
+        let idempotent = !url.ends_with("/items/moving");
+        let mut attempt = 1;
+        let mut renewed = false;
+        loop {
+            let session = self.session();
+            let error = match self.post_raw(url, &body).await {
+                Ok(res) => {
+                    let error = serde_json::from_str::<ErrorResponse>(&res)
+                        .ok()
+                        .and_then(|e| handle_error2(e).err());
+                    match error {
+                        // The request was rejected before doing anything, safe to send again.
+                        Some(Error::NotAuthorized(_)) if !renewed && self.can_renew() => {
+                            renewed = true;
+                            self.renew_expired(&session).await?;
+                            continue;
+                        }
+                        Some(e) if self.retry.should_retry(&e, attempt, idempotent) => e,
+                        _ => return Ok(serde_json::from_str::<T>(&res)?),
+                    }
+                }
+                Err(e) if self.retry.should_retry(&e, attempt, idempotent) => e,
+                Err(e) => return Err(e),
+            };
+
+            let delay = self.retry.delay(attempt);
+            warn!(
+                "Request to {} failed ({}), retrying in {:?}",
+                url, error, delay
+            );
+            tokio::time::delay_for(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Next transaction number (`tm`) of the client.
+    pub(crate) fn next_tm(&self) -> u64 {
+        self.sequence.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    /// Copy of the client sharing the same transport and activity tracking, without owning
+    /// the keep-alive task.
+    fn fork(&self) -> Tarkov {
+        Tarkov {
+            transport: self.transport.clone(),
+            config: self.config.clone(),
+            game_version: self.game_version.clone(),
+            retry: self.retry.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            last_request: self.last_request.clone(),
+            keep_alive: None,
+            state: self.state.clone(),
+            renewing: self.renewing.clone(),
+            renewals: self.renewals.clone(),
+            sequence: self.sequence.clone(),
+            moving: self.moving.clone(),
+            auto_renew: self.auto_renew,
+        }
+    }
+
+    async fn post_raw(&self, url: &str, body: &str) -> Result<String> {
         let req = Request::builder()
             .uri(url)
             .method(Method::POST)
@@ -249,24 +652,27 @@ impl Tarkov {
                 "User-Agent",
                 format!(
                     "UnityPlayer/{} (UnityWebRequest/1.0, libcurl/7.52.0-DEV)",
-                    UNITY_VERSION
+                    self.config.unity_version
                 ),
             )
-            .header("App-Version", format!("EFT Client {}", GAME_VERSION))
-            .header("X-Unity-Version", UNITY_VERSION)
-            .header("Cookie", format!("PHPSESSID={}", self.session))
-            .body(body)?;
-        let res = self.client.request(req).await?;
+            .header(
+                "App-Version",
+                format!("EFT Client {}", self.game_version.lock().unwrap()),
+            )
+            .header("X-Unity-Version", &self.config.unity_version)
+            .header("Cookie", format!("PHPSESSID={}", self.session()))
+            .body(body.as_bytes().to_vec())?;
+        *self.last_request.lock().unwrap() = Instant::now();
+        let res = self.transport.send(req).await?;
 
         match res.status() {
             StatusCode::OK => {
-                let body = hyper::body::to_bytes(res.into_body()).await?;
-                let mut decode = ZlibDecoder::new(body.bytes());
+                let mut decode = ZlibDecoder::new(res.body().as_slice());
                 let mut body = String::new();
                 decode.read_to_string(&mut body)?;
                 debug!("Response: {}", body);
 
-                Ok(serde_json::from_slice::<T>(body.as_bytes())?)
+                Ok(body)
             }
             _ => Err(Error::Status(res.status())),
         }
@@ -275,30 +681,44 @@ impl Tarkov {
 
 pub(crate) fn handle_error<T: DeserializeOwned>(error: ErrorResponse, ret: Option<T>) -> Result<T> {
     handle_error2(error)?;
-    Ok(ret.expect("API returned no errors but `data` is unavailable."))
+    ret.ok_or(Error::MissingData)
 }
 
 pub(crate) fn handle_error2(error: ErrorResponse) -> Result<()> {
     match error.code {
         0 => Ok(()),
-        201 => Err(Error::NotAuthorized)?,
-        205 => Err(ProfileError::InvalidUserID)?,
-        206 => Err(LoginError::BadLogin)?,
-        207 => Err(Error::InvalidParameters)?,
-        209 => Err(LoginError::TwoFactorRequired)?,
-        211 => Err(LoginError::BadTwoFactorCode)?,
-        214 => Err(LoginError::CaptchaRequired)?,
-        228 => Err(RagfairError::InvalidBarterItems)?,
-        230 => Err(LoginError::RateLimited)?,
-        232 => Err(LoginError::WrongMajorVersion)?,
-        263 => Err(Error::Maintenance)?,
-        1000 => Err(Error::BackendError)?,
-        1501 => Err(RagfairError::MaxOfferCount)?,
-        1502 => Err(RagfairError::InsufficientTaxFunds)?,
-        1507 => Err(RagfairError::OfferNotFound)?,
-        1510 => Err(TradingError::BadLoyaltyLevel)?,
-        1512 => Err(RagfairError::OfferNotAvailableYet)?,
-        1514 => Err(TradingError::TransactionError)?,
-        _ => Err(Error::UnknownAPIError(error.code)),
+        code => Err(api_error(code, ErrorMessage(error.message))),
+    }
+}
+
+/// Map a backend error code to an error. Codes specific to an API are listed in the `from_code`
+/// table of its error type.
+///
+/// Codes without a confirmed meaning, such as 550, 551, 1519 and the 7xx range, are left as
+/// `UnknownAPIError` with their `errmsg` instead of guessing.
+pub(crate) fn api_error(code: u64, message: ErrorMessage) -> Error {
+    if let Some(e) = LoginError::from_code(code) {
+        return Error::LoginError(e, message);
+    }
+    if let Some(e) = ProfileError::from_code(code) {
+        return Error::ProfileError(e, message);
+    }
+    if let Some(e) = TradingError::from_code(code) {
+        return Error::TradingError(e, message);
+    }
+    if let Some(e) = RagfairError::from_code(code) {
+        return Error::RagfairError(e, message);
+    }
+    if let Some(e) = InventoryError::from_code(code) {
+        return Error::InventoryError(e, message);
+    }
+
+    match code {
+        // 602: session lost.
+        201 | 602 => Error::NotAuthorized(message),
+        207 => Error::InvalidRequest(message),
+        263 => Error::Maintenance(message),
+        1000 => Error::BackendError(message),
+        _ => Error::UnknownAPIError(code, message),
     }
 }
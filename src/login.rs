@@ -0,0 +1,257 @@
+use crate::auth::{self, LoginError, QueueStatus};
+use crate::hwid::parse_hwid;
+use crate::session::SessionState;
+use crate::{Error, Result, Tarkov, TarkovBuilder};
+use tokio::sync::mpsc;
+
+/// State of a `LoginFlow`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginState {
+    /// Credentials were not sent yet, continue with `LoginFlow::submit`.
+    Credentials,
+    /// Captcha response is required, continue with `LoginFlow::captcha`.
+    CaptchaRequired,
+    /// 2FA code is required, continue with `LoginFlow::two_factor`.
+    TwoFactorRequired,
+    /// Waiting in the login queue, continue with `LoginFlow::poll` or `LoginFlow::wait_queue`.
+    Queued,
+    /// Logged in, get the client with `LoginFlow::finish`.
+    LoggedIn,
+}
+
+/// Resumable email and password login.
+///
+/// Each step sends what the previous state asked for and returns the next state. Failed steps
+/// leave the state unchanged, so they can be tried again.
+///
+/// ```no_run
+/// # async fn run() -> tarkov::Result<()> {
+/// use tarkov::hwid::generate_hwid;
+/// use tarkov::login::{LoginFlow, LoginState, QueueUpdate};
+///
+/// let mut flow = LoginFlow::new("me@example.com", "password", &generate_hwid());
+/// let mut state = flow.submit().await?;
+/// loop {
+///     state = match state {
+///         LoginState::CaptchaRequired => flow.captcha("03AOLTBLQ952pO-qQYPeLr53N5nK9Co14iXyCp...").await?,
+///         LoginState::TwoFactorRequired => flow.two_factor("XYZ").await?,
+///         _ => break,
+///     };
+/// }
+///
+/// if state == LoginState::Queued {
+///     let mut updates = flow.wait_queue();
+///     while let Some(update) = updates.recv().await {
+///         match update? {
+///             QueueUpdate::Waiting(status) => println!("Queue position: {}", status.position),
+///             QueueUpdate::Ready(t) => println!("Session: {}", t.session()),
+///         }
+///     }
+/// } else {
+///     let t = flow.finish()?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct LoginFlow {
+    builder: TarkovBuilder,
+    email: String,
+    password: String,
+    hwid: String,
+    state: LoginState,
+    session: Option<SessionState>,
+    queue: Option<QueueStatus>,
+}
+
+/// Update sent by `LoginFlow::wait_queue`.
+pub enum QueueUpdate {
+    /// Still waiting in the queue.
+    Waiting(QueueStatus),
+    /// Session is active.
+    Ready(Box<Tarkov>),
+}
+
+impl LoginFlow {
+    /// Start a login with the default client configuration.
+    pub fn new(email: &str, password: &str, hwid: &str) -> Self {
+        Tarkov::builder().login_flow(email, password, hwid)
+    }
+
+    /// Current state.
+    pub fn state(&self) -> LoginState {
+        self.state
+    }
+
+    /// Last known login queue status.
+    pub fn queue_status(&self) -> Option<&QueueStatus> {
+        self.queue.as_ref()
+    }
+
+    /// Send email and password.
+    pub async fn submit(&mut self) -> Result<LoginState> {
+        self.expect(LoginState::Credentials)?;
+        if self.email.is_empty() || self.password.is_empty() || self.hwid.is_empty() {
+            return Err(Error::InvalidParameters);
+        }
+        parse_hwid(&self.hwid)?;
+
+        self.login(None).await
+    }
+
+    /// Send email and password with a captcha response.
+    pub async fn captcha(&mut self, captcha: &str) -> Result<LoginState> {
+        self.expect(LoginState::CaptchaRequired)?;
+        if captcha.is_empty() {
+            return Err(Error::InvalidParameters);
+        }
+
+        self.login(Some(captcha)).await
+    }
+
+    /// Activate the hardware ID with a 2FA code, then log in again.
+    pub async fn two_factor(&mut self, code: &str) -> Result<LoginState> {
+        self.expect(LoginState::TwoFactorRequired)?;
+        if code.is_empty() {
+            return Err(Error::InvalidParameters);
+        }
+
+        auth::activate_hardware(
+            &*self.builder.transport,
+            &self.builder.config,
+            &self.email,
+            code,
+            &self.hwid,
+        )
+        .await?;
+        self.login(None).await
+    }
+
+    /// Check the login queue, and get the session once it is cleared.
+    pub async fn poll(&mut self) -> Result<LoginState> {
+        self.expect(LoginState::Queued)?;
+        let access_token = match self.session.as_ref().and_then(|s| s.access_token.clone()) {
+            Some(access_token) => access_token,
+            None => return Err(Error::UnexpectedLoginStep(self.state)),
+        };
+
+        let status = auth::get_queue_status(
+            &*self.builder.transport,
+            &self.builder.config,
+            &access_token,
+        )
+        .await?;
+        let queued = status.queued;
+        self.queue = Some(status);
+        if queued {
+            return Ok(self.state);
+        }
+
+        let session = self
+            .builder
+            .exchange_access_token(&access_token, &self.hwid, None)
+            .await?;
+        if let Some(state) = &mut self.session {
+            state.session = session.session;
+        }
+        if !session.queued {
+            self.state = LoginState::LoggedIn;
+        }
+
+        Ok(self.state)
+    }
+
+    /// Poll the login queue in a background task until the session is active.
+    ///
+    /// Every queue status is sent to the returned channel, followed by the logged in client. The
+    /// channel is closed after the client or an error was sent. Polling slows down with longer
+    /// ETAs, see `QueueStatus::poll_interval`. Must be called from within a tokio runtime.
+    pub fn wait_queue(mut self) -> mpsc::UnboundedReceiver<Result<QueueUpdate>> {
+        let (updates, rx) = mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            let update = loop {
+                match self.poll().await {
+                    Ok(LoginState::Queued) => {
+                        let status = match self.queue.clone() {
+                            Some(status) => status,
+                            None => break Err(Error::UnexpectedLoginStep(self.state)),
+                        };
+                        let interval = status.poll_interval();
+                        if updates.send(Ok(QueueUpdate::Waiting(status))).is_err() {
+                            return;
+                        }
+                        tokio::time::delay_for(interval).await;
+                    }
+                    Ok(_) => break self.finish().map(|t| QueueUpdate::Ready(Box::new(t))),
+                    Err(e) => break Err(e),
+                }
+            };
+            let _ = updates.send(update);
+        });
+
+        rx
+    }
+
+    /// Get the logged in client.
+    pub fn finish(self) -> Result<Tarkov> {
+        match (self.state, self.session) {
+            (LoginState::LoggedIn, Some(state)) => Ok(self.builder.build(state)),
+            (state, _) => Err(Error::UnexpectedLoginStep(state)),
+        }
+    }
+
+    fn expect(&self, state: LoginState) -> Result<()> {
+        if self.state != state {
+            return Err(Error::UnexpectedLoginStep(self.state));
+        }
+
+        Ok(())
+    }
+
+    async fn login(&mut self, captcha: Option<&str>) -> Result<LoginState> {
+        let res = auth::login(
+            &*self.builder.transport,
+            &self.builder.config,
+            &self.email,
+            &self.password,
+            captcha,
+            &self.hwid,
+        )
+        .await;
+
+        self.state = match res {
+            Ok(user) => {
+                let (session, state) = self.builder.start_session(user, &self.hwid).await?;
+                self.session = Some(state);
+                if session.queued {
+                    LoginState::Queued
+                } else {
+                    LoginState::LoggedIn
+                }
+            }
+            Err(Error::LoginError(LoginError::CaptchaRequired, _)) => LoginState::CaptchaRequired,
+            Err(Error::LoginError(LoginError::TwoFactorRequired, _)) => {
+                LoginState::TwoFactorRequired
+            }
+            Err(e) => return Err(e),
+        };
+
+        Ok(self.state)
+    }
+}
+
+impl TarkovBuilder {
+    /// Start a step-by-step login, see `LoginFlow`.
+    pub fn login_flow(mut self, email: &str, password: &str, hwid: &str) -> LoginFlow {
+        self.wrap_transport();
+        LoginFlow {
+            builder: self,
+            email: email.to_string(),
+            password: password.to_string(),
+            hwid: hwid.to_string(),
+            state: LoginState::Credentials,
+            session: None,
+            queue: None,
+        }
+    }
+}
@@ -0,0 +1,283 @@
+use crate::config::ClientConfig;
+use crate::transport::{Transport, TransportFuture};
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use http::{Request, Response, StatusCode};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::sync::Mutex;
+
+/// Session cookie handed out by the mock `game/start` endpoint.
+pub const MOCK_SESSION: &str = "e1bc65a216325f0ad0db8518fa299db2";
+/// Access token handed out by the mock `launcher/login` endpoint.
+pub const MOCK_ACCESS_TOKEN: &str = "mock-access-token";
+/// Refresh token handed out by the mock `launcher/login` and `launcher/token/refresh` endpoints.
+pub const MOCK_REFRESH_TOKEN: &str = "mock-refresh-token";
+/// PMC profile ID served by the mock profile list.
+pub const MOCK_PROFILE_ID: &str = "5e8e1c8c9c2e6e1a5c3f1a01";
+/// Trader ID served by the mock trading endpoints.
+pub const MOCK_TRADER_ID: &str = "54cb57776803fa99248b456e";
+/// Offer ID served by the mock flea market.
+pub const MOCK_OFFER_ID: &str = "5e8e1c8c9c2e6e1a5c3f1e01";
+
+/// Request received by the `MockServer`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockRequest {
+    /// Full request URL.
+    pub url: String,
+    /// Request JSON body.
+    pub body: Value,
+}
+
+/// In-memory EFT backend.
+///
+/// Routes are matched on the URL path, the endpoint host and query string are ignored. Every
+/// response is wrapped in the `{err, errmsg, data}` envelope and compressed with zlib, like the
+/// real API. Unknown routes return `404 Not Found`.
+///
+/// ```no_run
+/// # async fn run() -> tarkov::Result<()> {
+/// use std::sync::Arc;
+/// use tarkov::mock::{MockServer, MOCK_PROFILE_ID};
+/// use tarkov::Tarkov;
+///
+/// let server = Arc::new(MockServer::new());
+/// let t = Tarkov::builder()
+///     .transport(server.clone())
+///     .login("me@example.com", "password", &tarkov::hwid::generate_hwid())
+///     .await?;
+///
+/// t.select_profile(MOCK_PROFILE_ID).await?;
+/// assert_eq!(server.requests().len(), 3);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MockServer {
+    routes: Mutex<HashMap<String, (StatusCode, Value)>>,
+    once: Mutex<HashMap<String, VecDeque<(StatusCode, Value)>>>,
+    requests: Mutex<Vec<MockRequest>>,
+    game_version: Mutex<String>,
+}
+
+impl Default for MockServer {
+    fn default() -> Self {
+        MockServer::new()
+    }
+}
+
+impl MockServer {
+    /// Create a mock server with canned responses for the common endpoints.
+    pub fn new() -> Self {
+        let server = MockServer {
+            routes: Mutex::new(HashMap::new()),
+            once: Mutex::new(HashMap::new()),
+            requests: Mutex::new(Vec::new()),
+            game_version: Mutex::new(String::new()),
+        };
+
+        server.set_game_version(&ClientConfig::default().game_version);
+        server.respond("/launcher/hardwareCode/activate", Value::Null);
+        server.respond(
+            "/launcher/token/refresh",
+            json!({
+                "expires_in": 3600,
+                "access_token": MOCK_ACCESS_TOKEN,
+                "refresh_token": MOCK_REFRESH_TOKEN
+            }),
+        );
+        server.respond(
+            "/launcher/game/start",
+            json!({ "queued": false, "session": MOCK_SESSION }),
+        );
+        server.respond(
+            "/launcher/queue/status",
+            json!({ "queued": false, "position": 0, "waitTime": 0 }),
+        );
+        server.respond("/client/game/keepalive", Value::Null);
+        server.respond(
+            "/client/game/profile/list",
+            serde_json::from_str(include_str!("mock/profiles.json")).unwrap(),
+        );
+        server.respond(
+            "/client/game/profile/select",
+            json!({
+                "status": "ok",
+                "notifier": {
+                    "server": "notifier.escapefromtarkov.com",
+                    "channel_id": "mock-channel",
+                    "url": "https://notifier.escapefromtarkov.com/notifierServer/get/mock-channel"
+                },
+                "notifierServer": "https://notifier.escapefromtarkov.com/notifierServer/get/mock-channel"
+            }),
+        );
+
+        let traders: Value = serde_json::from_str(include_str!("mock/traders.json")).unwrap();
+        server.respond(
+            &format!("/client/trading/api/getTrader/{}", MOCK_TRADER_ID),
+            traders[0].clone(),
+        );
+        server.respond("/client/trading/api/getTradersList", traders);
+        server.respond(
+            &format!("/client/trading/api/getTraderAssort/{}", MOCK_TRADER_ID),
+            serde_json::from_str(include_str!("mock/trader_assort.json")).unwrap(),
+        );
+        server.respond(
+            &format!(
+                "/client/trading/api/getUserAssortPrice/trader/{}",
+                MOCK_TRADER_ID
+            ),
+            serde_json::from_str(include_str!("mock/trader_prices.json")).unwrap(),
+        );
+
+        server.respond(
+            "/client/ragfair/find",
+            serde_json::from_str(include_str!("mock/ragfair_find.json")).unwrap(),
+        );
+        server.respond(
+            "/client/ragfair/itemMarketPrice",
+            json!({
+                "templateId": "544fb37f4bdc2dee738b4567",
+                "min": 3500.0,
+                "max": 6000.0,
+                "avg": 4500.0
+            }),
+        );
+        server.respond(
+            "/client/game/profile/items/moving",
+            json!({
+                "items": { "new": [], "change": [], "del": [] },
+                "badRequest": []
+            }),
+        );
+
+        server
+    }
+
+    /// Change the game version advertised on login and by the launcher. `game/start` rejects
+    /// any other version with error code 232.
+    pub fn set_game_version(&self, version: &str) -> &Self {
+        *self.game_version.lock().unwrap() = version.to_string();
+        self.respond(
+            "/launcher/login",
+            json!({
+                "aid": "1234567",
+                "lang": "en",
+                "region": null,
+                "gameVersion": version,
+                "dataCenters": [],
+                "ipRegion": "CA",
+                "token_type": "Bearer",
+                "expires_in": 3600,
+                "access_token": MOCK_ACCESS_TOKEN,
+                "refresh_token": MOCK_REFRESH_TOKEN
+            }),
+        );
+        self.respond("/launcher/GetDistrib", json!({ "Version": version }))
+    }
+
+    /// Respond to `path` with `data` and no error.
+    pub fn respond(&self, path: &str, data: Value) -> &Self {
+        self.routes.lock().unwrap().insert(
+            path.to_string(),
+            (
+                StatusCode::OK,
+                json!({ "err": 0, "errmsg": null, "data": data }),
+            ),
+        );
+        self
+    }
+
+    /// Respond to `path` with an API error code and message.
+    pub fn fail(&self, path: &str, code: u64, message: &str) -> &Self {
+        self.routes.lock().unwrap().insert(
+            path.to_string(),
+            (
+                StatusCode::OK,
+                json!({ "err": code, "errmsg": message, "data": null }),
+            ),
+        );
+        self
+    }
+
+    /// Respond to `path` with a non-success HTTP status.
+    pub fn status(&self, path: &str, status: StatusCode) -> &Self {
+        self.routes
+            .lock()
+            .unwrap()
+            .insert(path.to_string(), (status, Value::Null));
+        self
+    }
+
+    /// Respond to the next request to `path` with an API error code and message. Queued
+    /// responses are served in order before falling back to the route.
+    pub fn fail_once(&self, path: &str, code: u64, message: &str) -> &Self {
+        self.once
+            .lock()
+            .unwrap()
+            .entry(path.to_string())
+            .or_default()
+            .push_back((
+                StatusCode::OK,
+                json!({ "err": code, "errmsg": message, "data": null }),
+            ));
+        self
+    }
+
+    /// Respond to the next request to `path` with a non-success HTTP status.
+    pub fn status_once(&self, path: &str, status: StatusCode) -> &Self {
+        self.once
+            .lock()
+            .unwrap()
+            .entry(path.to_string())
+            .or_default()
+            .push_back((status, Value::Null));
+        self
+    }
+
+    /// All requests received so far, oldest first.
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Transport for MockServer {
+    fn send(&self, request: Request<Vec<u8>>) -> TransportFuture<'_> {
+        let body: Value = serde_json::from_slice(request.body()).unwrap_or(Value::Null);
+        let route = if request.uri().path() == "/launcher/game/start"
+            && body["version"]["major"] != *self.game_version.lock().unwrap()
+        {
+            Some((
+                StatusCode::OK,
+                json!({ "err": 232, "errmsg": "wrong major version", "data": null }),
+            ))
+        } else {
+            self.once
+                .lock()
+                .unwrap()
+                .get_mut(request.uri().path())
+                .and_then(|queue| queue.pop_front())
+                .or_else(|| {
+                    self.routes
+                        .lock()
+                        .unwrap()
+                        .get(request.uri().path())
+                        .cloned()
+                })
+        };
+
+        self.requests.lock().unwrap().push(MockRequest {
+            url: request.uri().to_string(),
+            body,
+        });
+
+        Box::pin(async move {
+            let (status, body) = route.unwrap_or((StatusCode::NOT_FOUND, Value::Null));
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(serde_json::to_string(&body)?.as_bytes())?;
+
+            Ok(Response::builder().status(status).body(encoder.finish()?)?)
+        })
+    }
+}
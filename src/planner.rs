@@ -0,0 +1,470 @@
+use crate::batch::ActionBatch;
+use crate::constant;
+use crate::grid::{item_size, ContainerGrid, GridError};
+use crate::inventory::{Container, Item, Location};
+use crate::tree::InventoryTree;
+use std::collections::{HashMap, HashSet};
+
+#[cfg(test)]
+use crate::test_util::{test_db, test_tree};
+
+/// Step of a stash maintenance plan.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlanStep {
+    /// Merge the whole stack of `item_id` into the stack of `with_id`.
+    Merge {
+        /// Stack to empty
+        item_id: String,
+        /// Stack to fill
+        with_id: String,
+    },
+    /// Move `count` items from the stack of `item_id` to the stack of `with_id`.
+    Transfer {
+        /// Stack to take from
+        item_id: String,
+        /// Stack to fill
+        with_id: String,
+        /// Number of items
+        count: u64,
+    },
+    /// Move `item_id` to `location` in the planned grid.
+    Move {
+        /// Item to move
+        item_id: String,
+        /// New location
+        location: Location,
+    },
+}
+
+/// Merge and move steps that pack partial stacks and compact the items of a container grid.
+///
+/// Steps are ordered so they can be sent one after another, or together with `to_batch`.
+///
+/// ```no_run
+/// # async fn run(t: tarkov::Tarkov) -> tarkov::Result<()> {
+/// use tarkov::planner::Plan;
+/// use tarkov::tree::{InventoryRoot, InventoryTree};
+///
+/// let db = t.get_items().await?;
+/// let profile = t.get_profiles().await?.remove(0);
+/// let tree = InventoryTree::new(&profile.inventory);
+/// let stash = tree.root_id(InventoryRoot::Stash).unwrap();
+/// let plan = Plan::new(&tree, &db, stash, "hideout").unwrap();
+///
+/// // Dry run
+/// let grid = plan.simulate(&tree, &db).unwrap();
+/// println!("{} steps, {} free cells", plan.steps().len(), grid.free_cells());
+///
+/// if !plan.is_empty() {
+///     t.send_batch(&plan.to_batch()).await?.into_result()?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+    container_id: String,
+    grid: String,
+    steps: Vec<PlanStep>,
+}
+
+impl Plan {
+    /// Plan the maintenance of the grid `grid` of `container_id`, e.g. the `hideout` grid of
+    /// the stash.
+    ///
+    /// Partial stacks of the same template and found-in-raid status are packed first, into as
+    /// few stacks as their total needs and with as few steps as possible. Remaining items are
+    /// then moved, row by row, to the first free location before their own.
+    pub fn new(
+        tree: &InventoryTree,
+        db: &HashMap<String, constant::Item>,
+        container_id: &str,
+        grid: &str,
+    ) -> Result<Self, GridError> {
+        let mut cells = ContainerGrid::from_container(tree, db, container_id, grid)?;
+        let mut steps = Vec::new();
+
+        let mut stacks: HashMap<(&str, bool), Vec<(&str, u64)>> = HashMap::new();
+        for item in tree.children_in(container_id, grid) {
+            let stack_max = stack_max_size(db, &item.schema_id)?;
+            let count = stack_count(item);
+            if stack_max > 1 && count < stack_max && !is_unlimited(item) {
+                let found_in_raid = item
+                    .upd
+                    .as_ref()
+                    .and_then(|upd| upd.spawned_in_session)
+                    .unwrap_or(false);
+                stacks
+                    .entry((&item.schema_id, found_in_raid))
+                    .or_default()
+                    .push((&item.id, count));
+            }
+        }
+
+        let mut groups: Vec<_> = stacks.into_iter().collect();
+        groups.sort();
+        for ((schema_id, _), mut group) in groups {
+            let stack_max = stack_max_size(db, schema_id)?;
+            group.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+            steps.extend(pack_stacks(&group, stack_max));
+        }
+
+        let mut merged = HashSet::new();
+        for step in &steps {
+            if let PlanStep::Merge { item_id, .. } = step {
+                cells.remove(item_id);
+                merged.insert(item_id.clone());
+            }
+        }
+
+        let mut items: Vec<(&Item, &Location)> = Vec::new();
+        for item in tree.children_in(container_id, grid) {
+            if merged.contains(&item.id) {
+                continue;
+            }
+            let location = item
+                .location
+                .as_ref()
+                .ok_or_else(|| GridError::MissingLocation(item.id.clone()))?;
+            items.push((item, location));
+        }
+        items.sort_by_key(|(item, location)| (location.y, location.x, &item.id));
+
+        for (item, location) in items {
+            let size = item_size(tree, db, &item.id)?;
+            cells.remove(&item.id);
+            let target = match cells.find_space(size) {
+                Some(target) if (target.y, target.x) < (location.y, location.x) => target,
+                _ => location.clone(),
+            };
+            cells.place(&item.id, &target, size)?;
+            if &target != location {
+                steps.push(PlanStep::Move {
+                    item_id: item.id.clone(),
+                    location: target,
+                });
+            }
+        }
+
+        Ok(Plan {
+            container_id: container_id.to_string(),
+            grid: grid.to_string(),
+            steps,
+        })
+    }
+
+    /// Planned steps, in order.
+    pub fn steps(&self) -> &[PlanStep] {
+        &self.steps
+    }
+
+    /// Returns `true` if the grid needs no maintenance.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Batch of every step, to be sent with `Tarkov::send_batch`.
+    pub fn to_batch(&self) -> ActionBatch {
+        let mut batch = ActionBatch::new();
+        for step in &self.steps {
+            match step {
+                PlanStep::Merge { item_id, with_id } => batch.merge(item_id, with_id),
+                PlanStep::Transfer {
+                    item_id,
+                    with_id,
+                    count,
+                } => batch.transfer(item_id, with_id, *count),
+                PlanStep::Move { item_id, location } => batch.move_item(
+                    item_id,
+                    &Container::grid(&self.container_id, &self.grid, location.clone()),
+                ),
+            };
+        }
+
+        batch
+    }
+
+    /// Dry run: the grid as it would be after every step, without sending anything.
+    pub fn simulate(
+        &self,
+        tree: &InventoryTree,
+        db: &HashMap<String, constant::Item>,
+    ) -> Result<ContainerGrid, GridError> {
+        let mut cells = ContainerGrid::from_container(tree, db, &self.container_id, &self.grid)?;
+        for step in &self.steps {
+            match step {
+                PlanStep::Merge { item_id, .. } => cells.remove(item_id),
+                PlanStep::Transfer { .. } => {}
+                PlanStep::Move { item_id, location } => {
+                    let size = item_size(tree, db, item_id)?;
+                    cells.remove(item_id);
+                    cells.place(item_id, location, size)?;
+                }
+            }
+        }
+
+        Ok(cells)
+    }
+}
+
+/// Largest group of partial stacks searched exhaustively for the stacks to keep. Larger groups
+/// keep their fullest stacks.
+const MAX_SEARCHED_STACKS: usize = 12;
+
+/// Steps packing `group`, sorted by count descending, into as many stacks as its total needs.
+///
+/// Every emptied stack takes one `Merge`, so the number of merges is fixed. The stacks to keep
+/// are chosen to need the fewest `Transfer` steps, which are only added when no kept stack has
+/// room for the whole remainder of a stack.
+fn pack_stacks(group: &[(&str, u64)], stack_max: u64) -> Vec<PlanStep> {
+    let total: u64 = group.iter().map(|(_, count)| count).sum();
+    let keep = match total % stack_max {
+        0 => total / stack_max,
+        _ => total / stack_max + 1,
+    } as usize;
+    if keep >= group.len() {
+        return Vec::new();
+    }
+
+    let mut best: Option<Vec<PlanStep>> = None;
+    for kept in kept_stacks(group.len(), keep) {
+        let steps = empty_stacks(group, &kept, stack_max);
+        if best.as_ref().map_or(true, |best| steps.len() < best.len()) {
+            best = Some(steps);
+        }
+    }
+
+    best.unwrap_or_default()
+}
+
+/// Indices of the stacks to keep to try, fullest stacks first.
+fn kept_stacks(len: usize, keep: usize) -> Vec<Vec<usize>> {
+    let mut kept: Vec<usize> = (0..keep).collect();
+    if len > MAX_SEARCHED_STACKS {
+        return vec![kept];
+    }
+
+    let mut all = Vec::new();
+    loop {
+        all.push(kept.clone());
+        let i = match (0..keep).rev().find(|&i| kept[i] < len - keep + i) {
+            Some(i) => i,
+            None => return all,
+        };
+        kept[i] += 1;
+        for j in i + 1..keep {
+            kept[j] = kept[j - 1] + 1;
+        }
+    }
+}
+
+/// Steps emptying every stack of `group` not in `kept`, fullest first. A whole stack goes to
+/// the kept stack with the least room that fits it, otherwise the roomiest kept stack is filled.
+fn empty_stacks(group: &[(&str, u64)], kept: &[usize], stack_max: u64) -> Vec<PlanStep> {
+    let mut room: Vec<(usize, u64)> = kept.iter().map(|&i| (i, stack_max - group[i].1)).collect();
+    let mut steps = Vec::new();
+    for (i, &(item_id, mut count)) in group.iter().enumerate() {
+        if kept.contains(&i) {
+            continue;
+        }
+
+        loop {
+            let fit = room
+                .iter_mut()
+                .filter(|(_, room)| *room >= count)
+                .min_by_key(|(to, room)| (*room, *to));
+            if let Some((to, room)) = fit {
+                *room -= count;
+                steps.push(PlanStep::Merge {
+                    item_id: item_id.to_string(),
+                    with_id: group[*to].0.to_string(),
+                });
+                break;
+            }
+
+            // The kept stacks have room for the whole group, so some room is left.
+            let (to, room) = room
+                .iter_mut()
+                .max_by_key(|(to, room)| (*room, std::cmp::Reverse(*to)))
+                .unwrap();
+            steps.push(PlanStep::Transfer {
+                item_id: item_id.to_string(),
+                with_id: group[*to].0.to_string(),
+                count: *room,
+            });
+            count -= *room;
+            *room = 0;
+        }
+    }
+
+    steps
+}
+
+fn stack_max_size(db: &HashMap<String, constant::Item>, schema_id: &str) -> Result<u64, GridError> {
+    let template = db
+        .get(schema_id)
+        .ok_or_else(|| GridError::UnknownTemplate(schema_id.to_string()))?;
+    Ok(template.props.stack_max_size.unwrap_or(1))
+}
+
+fn stack_count(item: &Item) -> u64 {
+    item.upd
+        .as_ref()
+        .and_then(|upd| upd.stack_objects_count)
+        .unwrap_or(1)
+}
+
+fn is_unlimited(item: &Item) -> bool {
+    item.upd.as_ref().and_then(|upd| upd.unlimited_count) == Some(true)
+}
+
+#[cfg(test)]
+fn ammo_tree() -> InventoryTree {
+    let item = |id: &str, tpl: &str, (x, y): (u64, u64), count: u64, found_in_raid: bool| {
+        serde_json::json!({
+            "_id": id,
+            "_tpl": tpl,
+            "parentId": "stash",
+            "slotId": "hideout",
+            "location": { "x": x, "y": y, "r": 0 },
+            "upd": { "StackObjectsCount": count, "SpawnedInSession": found_in_raid },
+        })
+    };
+
+    test_tree(vec![
+        item("a", "ammo", (0, 0), 50, false),
+        item("b", "ammo", (2, 0), 30, false),
+        item("c", "ammo", (1, 2), 20, false),
+        item("d", "ammo", (3, 1), 10, true),
+        item("box", "box", (2, 2), 1, false),
+    ])
+}
+
+#[test]
+fn test_plan() {
+    let db = test_db();
+    let tree = ammo_tree();
+    let plan = Plan::new(&tree, &db, "stash", "hideout").unwrap();
+    let location = |x, y, r| Location {
+        x,
+        y,
+        r,
+        is_searched: None,
+    };
+
+    // Found in raid stacks are kept apart.
+    assert_eq!(
+        plan.steps(),
+        &[
+            PlanStep::Merge {
+                item_id: "c".to_string(),
+                with_id: "b".to_string(),
+            },
+            PlanStep::Move {
+                item_id: "b".to_string(),
+                location: location(1, 0, 0),
+            },
+            PlanStep::Move {
+                item_id: "d".to_string(),
+                location: location(2, 0, 0),
+            },
+            PlanStep::Move {
+                item_id: "box".to_string(),
+                location: location(3, 0, 1),
+            },
+        ]
+    );
+    assert_eq!(plan.to_batch().len(), 4);
+
+    let grid = plan.simulate(&tree, &db).unwrap();
+    assert_eq!(grid.occupant(1, 0), Some("b"));
+    assert_eq!(grid.occupant(2, 0), Some("d"));
+    assert_eq!(grid.occupant(3, 1), Some("box"));
+    assert_eq!(grid.free_cells(), 7);
+}
+
+#[test]
+fn test_pack_stacks() {
+    let merge = |item_id: &str, with_id: &str| PlanStep::Merge {
+        item_id: item_id.to_string(),
+        with_id: with_id.to_string(),
+    };
+
+    assert_eq!(
+        pack_stacks(&[("a", 50), ("b", 30), ("c", 20)], 60),
+        [merge("c", "b")]
+    );
+    assert_eq!(
+        pack_stacks(&[("a", 40), ("b", 40), ("c", 40)], 60),
+        [
+            PlanStep::Transfer {
+                item_id: "c".to_string(),
+                with_id: "a".to_string(),
+                count: 20,
+            },
+            merge("c", "b"),
+        ]
+    );
+    // Keeping the two fullest stacks would need a transfer.
+    assert_eq!(
+        pack_stacks(&[("a", 3), ("b", 3), ("c", 2), ("d", 2), ("e", 2)], 6),
+        [merge("b", "a"), merge("d", "c"), merge("e", "c")]
+    );
+    assert!(pack_stacks(&[("a", 40), ("b", 30)], 60).is_empty());
+}
+
+#[test]
+fn test_plan_applied() {
+    let db = test_db();
+    let mut tree = ammo_tree();
+    let plan = Plan::new(&tree, &db, "stash", "hideout").unwrap();
+
+    // Apply the plan as the server would, planning again has nothing left to do.
+    let mut counts: HashMap<String, u64> = tree
+        .items()
+        .map(|item| (item.id.clone(), stack_count(item)))
+        .collect();
+    let mut locations = HashMap::new();
+    let mut deleted = Vec::new();
+    for step in plan.steps() {
+        match step {
+            PlanStep::Merge { item_id, with_id } => {
+                let count = counts.remove(item_id).unwrap();
+                *counts.get_mut(with_id).unwrap() += count;
+                deleted.push(serde_json::json!({ "_id": item_id }));
+            }
+            PlanStep::Transfer {
+                item_id,
+                with_id,
+                count,
+            } => {
+                *counts.get_mut(item_id).unwrap() -= count;
+                *counts.get_mut(with_id).unwrap() += count;
+            }
+            PlanStep::Move { item_id, location } => {
+                locations.insert(item_id.clone(), location.clone());
+            }
+        }
+    }
+
+    let mut changed = Vec::new();
+    for item in tree.children("stash") {
+        let mut item = item.clone();
+        if let (Some(upd), Some(count)) = (item.upd.as_mut(), counts.get(&item.id)) {
+            upd.stack_objects_count = Some(*count);
+        }
+        if let Some(location) = locations.get(&item.id) {
+            item.location = Some(location.clone());
+        }
+        if counts.contains_key(&item.id) {
+            changed.push(item);
+        }
+    }
+    let update = serde_json::json!({ "change": changed, "del": deleted });
+    tree.apply(&serde_json::from_value(update).unwrap())
+        .unwrap();
+
+    assert!(Plan::new(&tree, &db, "stash", "hideout")
+        .unwrap()
+        .is_empty());
+}
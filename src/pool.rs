@@ -0,0 +1,199 @@
+use crate::{Error, Result, Tarkov};
+use log::warn;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Boxed future returned by the closures passed to `TarkovPool::run`.
+pub type PoolFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// Request statistics of a pool member.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AccountHealth {
+    /// Requests routed to the account.
+    pub requests: u64,
+    /// Failed requests, including keep-alive failures.
+    pub failures: u64,
+    /// Failed requests in a row. Reset by the next success.
+    pub consecutive_failures: u64,
+    /// Sessions renewed successfully by the client, see `Tarkov::renew_session`.
+    pub reauthentications: u64,
+    /// Last error, if any.
+    pub last_error: Option<String>,
+}
+
+impl AccountHealth {
+    /// Returns `true` if the last request succeeded.
+    pub fn is_healthy(&self) -> bool {
+        self.consecutive_failures == 0
+    }
+
+    fn record<T>(&mut self, res: &Result<T>) {
+        match res {
+            Ok(_) => self.consecutive_failures = 0,
+            Err(e) => {
+                self.failures += 1;
+                self.consecutive_failures += 1;
+                self.last_error = Some(e.to_string());
+            }
+        }
+    }
+}
+
+struct Member {
+    account: String,
+    client: Tarkov,
+    health: Arc<Mutex<AccountHealth>>,
+}
+
+/// Authenticated clients for several accounts.
+///
+/// Every client keeps its own session, keep-alive and rate limiter, configure them with
+/// `Tarkov::builder` before adding the client. Requests are routed round-robin with `run`, or to
+/// an explicit account with `run_on`. Expired sessions are renewed by the clients themselves once
+/// a profile is selected, see `Tarkov::renew_session`.
+///
+/// ```no_run
+/// # async fn run() -> tarkov::Result<()> {
+/// use tarkov::hwid::generate_hwid;
+/// use tarkov::pool::TarkovPool;
+/// use tarkov::Tarkov;
+///
+/// let mut pool = TarkovPool::new();
+/// pool.add("main", Tarkov::login("me@example.com", "password", &generate_hwid()).await?);
+/// pool.add("alt", Tarkov::login("alt@example.com", "password", &generate_hwid()).await?);
+///
+/// let traders = pool.run(|t| Box::pin(t.get_traders())).await?;
+/// let profiles = pool.run_on("alt", |t| Box::pin(t.get_profiles())).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct TarkovPool {
+    members: Vec<Member>,
+    next: AtomicUsize,
+}
+
+impl TarkovPool {
+    /// Create an empty pool.
+    pub fn new() -> Self {
+        TarkovPool::default()
+    }
+
+    /// Add the client for `account`, replacing any previous client for the account.
+    pub fn add(&mut self, account: &str, client: Tarkov) {
+        let member = Member {
+            account: account.to_string(),
+            client,
+            health: Arc::new(Mutex::new(AccountHealth::default())),
+        };
+
+        match self.members.iter_mut().find(|m| m.account == account) {
+            Some(m) => *m = member,
+            None => self.members.push(member),
+        }
+    }
+
+    /// Remove the client for `account`.
+    pub fn remove(&mut self, account: &str) -> Option<Tarkov> {
+        let i = self.members.iter().position(|m| m.account == account)?;
+        Some(self.members.remove(i).client)
+    }
+
+    /// Client for `account`.
+    pub fn get(&self, account: &str) -> Option<&Tarkov> {
+        self.member(account).ok().map(|m| &m.client)
+    }
+
+    /// Accounts in the pool, in the order they were added.
+    pub fn accounts(&self) -> Vec<&str> {
+        self.members.iter().map(|m| m.account.as_str()).collect()
+    }
+
+    /// Number of accounts in the pool.
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Returns `true` if the pool has no accounts.
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Request statistics of every account.
+    pub fn health(&self) -> HashMap<String, AccountHealth> {
+        self.members
+            .iter()
+            .map(|m| {
+                let mut health = m.health.lock().unwrap().clone();
+                health.reauthentications = m.client.renewals();
+                (m.account.clone(), health)
+            })
+            .collect()
+    }
+
+    /// Spawn a keep-alive task for every account, see `Tarkov::spawn_keep_alive`. Failures are
+    /// recorded in the account health.
+    pub fn spawn_keep_alive(&mut self, interval: Duration) {
+        for member in &mut self.members {
+            let mut errors = member.client.spawn_keep_alive(interval);
+            let health = member.health.clone();
+            let account = member.account.clone();
+            tokio::spawn(async move {
+                while let Some(e) = errors.recv().await {
+                    warn!("Keep-alive failed for {}: {}", account, e);
+                    health.lock().unwrap().record::<()>(&Err(e));
+                }
+            });
+        }
+    }
+
+    /// Run `f` with the next healthy account, round-robin. Falls back to unhealthy accounts if
+    /// none is healthy.
+    pub async fn run<F, T>(&self, f: F) -> Result<T>
+    where
+        F: for<'a> Fn(&'a Tarkov) -> PoolFuture<'a, T>,
+    {
+        if self.members.is_empty() {
+            return Err(Error::EmptyPool);
+        }
+
+        let start = self.next.fetch_add(1, Ordering::SeqCst);
+        let len = self.members.len();
+        let member = (0..len)
+            .map(|i| &self.members[(start + i) % len])
+            .find(|m| m.health.lock().unwrap().is_healthy())
+            .unwrap_or(&self.members[start % len]);
+
+        self.run_member(member, f).await
+    }
+
+    /// Run `f` with the client for `account`.
+    pub async fn run_on<F, T>(&self, account: &str, f: F) -> Result<T>
+    where
+        F: for<'a> Fn(&'a Tarkov) -> PoolFuture<'a, T>,
+    {
+        let member = self.member(account)?;
+        self.run_member(member, f).await
+    }
+
+    fn member(&self, account: &str) -> Result<&Member> {
+        self.members
+            .iter()
+            .find(|m| m.account == account)
+            .ok_or_else(|| Error::UnknownAccount(account.to_string()))
+    }
+
+    async fn run_member<F, T>(&self, member: &Member, f: F) -> Result<T>
+    where
+        F: for<'a> Fn(&'a Tarkov) -> PoolFuture<'a, T>,
+    {
+        member.health.lock().unwrap().requests += 1;
+        let res = f(&member.client).await;
+        member.health.lock().unwrap().record(&res);
+        res
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{handle_error, Error, ErrorResponse, Result, Tarkov, PROD_ENDPOINT};
+use crate::{handle_error, Error, ErrorResponse, Result, Tarkov};
 
 use crate::bad_json::deserialize_integer_to_string;
 use crate::inventory::Item;
@@ -495,6 +495,32 @@ pub enum ProfileError {
     /// Failed selecting profile.
     #[error(display = "select profile failed")]
     SelectProfileFail,
+    /// Nickname is already taken.
+    #[error(display = "nickname is already taken")]
+    NicknameTaken,
+    /// Nickname contains invalid characters or has an invalid length.
+    #[error(display = "invalid nickname")]
+    InvalidNickname,
+    /// Nickname was rejected as abusive.
+    #[error(display = "nickname is abusive")]
+    AbusiveNickname,
+    /// Nickname was changed too recently.
+    #[error(display = "nickname cannot be changed yet")]
+    NicknameChangeTimeout,
+}
+
+impl ProfileError {
+    /// Map a backend error code of the profile API.
+    pub(crate) fn from_code(code: u64) -> Option<Self> {
+        match code {
+            205 => Some(ProfileError::InvalidUserID),
+            225 => Some(ProfileError::NicknameTaken),
+            226 => Some(ProfileError::InvalidNickname),
+            241 => Some(ProfileError::AbusiveNickname),
+            242 => Some(ProfileError::NicknameChangeTimeout),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -535,7 +561,7 @@ pub struct Notifier {
 impl Tarkov {
     /// Get a list of account's profiles.
     pub async fn get_profiles(&self) -> Result<Vec<Profile>> {
-        let url = format!("{}/client/game/profile/list", PROD_ENDPOINT);
+        let url = format!("{}/client/game/profile/list", self.config.prod_endpoint);
         let res: ProfileResponse = self.post_json(&url, &{}).await?;
 
         handle_error(res.error, res.data)
@@ -547,7 +573,7 @@ impl Tarkov {
             return Err(Error::InvalidParameters);
         }
 
-        let url = format!("{}/client/game/profile/select", PROD_ENDPOINT);
+        let url = format!("{}/client/game/profile/select", self.config.prod_endpoint);
         let res: SelectResponse = self
             .post_json(&url, &SelectRequest { uid: user_id })
             .await?;
@@ -557,6 +583,10 @@ impl Tarkov {
             return Err(ProfileError::SelectProfileFail)?;
         }
 
+        let mut state = self.state.lock().unwrap();
+        state.profile_id = Some(user_id.to_string());
+        state.notifier = Some(res.notifier);
+
         Ok(())
     }
 }
@@ -1,9 +1,7 @@
-use crate::{
-    handle_error, handle_error2, Error, ErrorResponse, Result, Tarkov, PROD_ENDPOINT,
-    RAGFAIR_ENDPOINT,
-};
+use crate::{handle_error, Error, ErrorResponse, Result, Tarkov};
 
-use crate::inventory::{BarterItem, InventoryUpdate, Item, MoveItemRequest, RagfairResponseData};
+use crate::batch::ActionBatch;
+use crate::inventory::{BarterItem, InventoryUpdate, Item};
 use crate::market_filter::{Currency, MarketFilter, Owner, SortBy, SortDirection};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -26,6 +24,40 @@ pub enum RagfairError {
     /// Insufficient funds to pay the flea market fee.
     #[error(display = "insufficient funds to pay market fee")]
     InsufficientTaxFunds,
+    /// Not enough space in the stash for the purchased items.
+    #[error(display = "not enough space in stash")]
+    NotEnoughSpace,
+    /// Flea market is not available, usually because the character level is too low.
+    #[error(display = "flea market is unavailable")]
+    Unavailable,
+    /// Banned from the flea market.
+    #[error(display = "banned from the flea market")]
+    Banned,
+    /// Offer does not have the requested quantity in stock.
+    #[error(display = "insufficient quantity in stock")]
+    InsufficientStock,
+    /// Too many items in a single offer.
+    #[error(display = "too many items to sell")]
+    TooManyItems,
+}
+
+impl RagfairError {
+    /// Map a backend error code of the flea market API.
+    pub(crate) fn from_code(code: u64) -> Option<Self> {
+        match code {
+            228 => Some(RagfairError::InvalidBarterItems),
+            1501 => Some(RagfairError::MaxOfferCount),
+            1502 => Some(RagfairError::InsufficientTaxFunds),
+            1503 | 1506 | 1507 => Some(RagfairError::OfferNotFound),
+            1505 => Some(RagfairError::NotEnoughSpace),
+            1511 => Some(RagfairError::Unavailable),
+            1512 => Some(RagfairError::OfferNotAvailableYet),
+            1513 => Some(RagfairError::Banned),
+            1516 => Some(RagfairError::InsufficientStock),
+            1517 => Some(RagfairError::TooManyItems),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -142,31 +174,17 @@ pub struct Requirement {
 }
 
 #[derive(Debug, Serialize)]
-struct BuyItemRequest<'a> {
+pub(crate) struct BuyItemRequest {
     #[serde(rename = "Action")]
-    action: &'a str,
-    offers: &'a [BuyOffer<'a>],
+    pub(crate) action: &'static str,
+    pub(crate) offers: Vec<BuyOffer>,
 }
 
 #[derive(Debug, Serialize)]
-struct BuyOffer<'a> {
-    id: &'a str,
-    count: u64,
-    items: &'a [BarterItem],
-}
-
-#[derive(Debug, Serialize)]
-struct BuyItem {
-    item: String,
-    count: f64,
-}
-
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct BuyItemResponse {
-    #[serde(flatten)]
-    error: ErrorResponse,
-    data: serde_json::Value,
+pub(crate) struct BuyOffer {
+    pub(crate) id: String,
+    pub(crate) count: u64,
+    pub(crate) items: Vec<BarterItem>,
 }
 
 #[derive(Debug, Serialize)]
@@ -199,35 +217,27 @@ pub struct Price {
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
-struct SellItemRequest<'a> {
+pub(crate) struct OfferItemRequest {
     #[serde(rename = "Action")]
-    action: &'a str,
-    sell_in_one_piece: bool,
-    items: &'a [&'a str],
-    requirements: &'a [SellRequirement],
+    pub(crate) action: &'static str,
+    pub(crate) sell_in_one_piece: bool,
+    pub(crate) items: Vec<String>,
+    pub(crate) requirements: Vec<SellRequirement>,
 }
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
-struct SellRequirement {
-    #[serde(flatten)]
-    requirement: Requirement,
-    level: u64,
-    side: u8,
-    only_functional: bool,
-}
-
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct SellItemResponse {
+pub(crate) struct SellRequirement {
     #[serde(flatten)]
-    error: ErrorResponse,
-    data: serde_json::Value,
+    pub(crate) requirement: Requirement,
+    pub(crate) level: u64,
+    pub(crate) side: u8,
+    pub(crate) only_functional: bool,
 }
 
 impl Tarkov {
     /// Search the flea market.
-    pub async fn search_market<'a>(
+    pub async fn search_market(
         &self,
         page: u64,
         limit: u64,
@@ -254,13 +264,13 @@ impl Tarkov {
             offer_owner_type: filter.owner_type,
             only_functional: filter.hide_inoperable_weapons,
             update_offer_count: true,
-            handbook_id: &filter.handbook_id.unwrap_or(""),
-            linked_search_id: &filter.linked_search_id.unwrap_or(""),
-            needed_search_id: &filter.required_search_id.unwrap_or(""),
-            tm: 1,
+            handbook_id: filter.handbook_id.unwrap_or(""),
+            linked_search_id: filter.linked_search_id.unwrap_or(""),
+            needed_search_id: filter.required_search_id.unwrap_or(""),
+            tm: self.next_tm(),
         };
 
-        let url = format!("{}/client/ragfair/find", RAGFAIR_ENDPOINT);
+        let url = format!("{}/client/ragfair/find", self.config.ragfair_endpoint);
         let res: SearchResponse = self.post_json(&url, &body).await?;
 
         handle_error(res.error, res.data)
@@ -272,7 +282,10 @@ impl Tarkov {
             return Err(Error::InvalidParameters);
         }
 
-        let url = format!("{}/client/ragfair/itemMarketPrice", RAGFAIR_ENDPOINT);
+        let url = format!(
+            "{}/client/ragfair/itemMarketPrice",
+            self.config.ragfair_endpoint
+        );
         let body = GetPriceRequest {
             template_id: schema_id,
         };
@@ -292,33 +305,9 @@ impl Tarkov {
             return Err(Error::InvalidParameters);
         }
 
-        let url = format!("{}/client/game/profile/items/moving", PROD_ENDPOINT);
-        let body = &MoveItemRequest {
-            data: &[BuyItemRequest {
-                action: "RagFairBuyOffer",
-                offers: &[BuyOffer {
-                    id: offer_id,
-                    count: quantity,
-                    items: barter_items,
-                }],
-            }],
-            tm: 2,
-        };
-
-        let res: BuyItemResponse = self.post_json(&url, body).await?;
-        handle_error2(res.error)?;
-
-        let res: RagfairResponseData = Deserialize::deserialize(res.data)?;
-        if !res.errors.is_empty() {
-            let error = &res.errors[0];
-            match error.code {
-                1503 | 1506 | 1507 => return Err(RagfairError::OfferNotFound)?,
-                _ => return Err(Error::UnknownAPIError(error.code)),
-            }
-        }
-
-        let items: InventoryUpdate = Deserialize::deserialize(res.items)?;
-        Ok(items)
+        let mut batch = ActionBatch::new();
+        batch.buy_offer(offer_id, quantity, barter_items);
+        self.send_single(batch).await
     }
 
     /// List an item for sale on the flea market.
@@ -332,35 +321,8 @@ impl Tarkov {
             return Err(Error::InvalidParameters);
         }
 
-        let url = format!("{}/client/game/profile/items/moving", PROD_ENDPOINT);
-        let body = &MoveItemRequest {
-            data: &[SellItemRequest {
-                action: "RagFairAddOffer",
-                sell_in_one_piece: sell_all,
-                items,
-                requirements: &requirements
-                    .into_iter()
-                    .map(|r| SellRequirement {
-                        requirement: r.to_owned(),
-                        level: 0,
-                        side: 0,
-                        only_functional: false,
-                    })
-                    .collect::<Vec<SellRequirement>>(),
-            }],
-            tm: 2,
-        };
-
-        let res: SellItemResponse = self.post_json(&url, body).await?;
-        handle_error2(res.error)?;
-
-        let res: RagfairResponseData = Deserialize::deserialize(res.data)?;
-        if !res.errors.is_empty() {
-            let error = &res.errors[0];
-            return Err(Error::UnknownAPIError(error.code));
-        }
-
-        let items: InventoryUpdate = Deserialize::deserialize(res.items)?;
-        Ok(items)
+        let mut batch = ActionBatch::new();
+        batch.add_offer(items, requirements, sell_all);
+        self.send_single(batch).await
     }
 }
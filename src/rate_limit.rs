@@ -0,0 +1,222 @@
+use crate::config::ClientConfig;
+use crate::transport::{Transport, TransportFuture};
+use http::{Request, Uri};
+use log::debug;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Token bucket budget for a single host.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// Sustained requests per second.
+    pub requests_per_second: f64,
+    /// Requests that can be sent at once after being idle.
+    pub burst: u32,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    limit: RateLimit,
+    tokens: f64,
+    updated: Instant,
+    queued: Arc<AtomicUsize>,
+}
+
+impl Bucket {
+    fn new(limit: RateLimit) -> Self {
+        Bucket {
+            limit,
+            tokens: f64::from(limit.burst),
+            updated: Instant::now(),
+            queued: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Reserve a token and return how long to wait before using it.
+    fn reserve(&mut self) -> Duration {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.limit.requests_per_second)
+            .min(f64::from(self.limit.burst));
+        self.updated = now;
+        self.tokens -= 1.0;
+
+        if self.tokens >= 0.0 {
+            Duration::from_secs(0)
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.limit.requests_per_second)
+        }
+    }
+}
+
+/// Client-side rate limiter with one token bucket per endpoint host.
+///
+/// Requests to hosts without a configured limit are sent immediately.
+///
+/// ```
+/// use tarkov::config::ClientConfig;
+/// use tarkov::rate_limit::{RateLimit, RateLimiter};
+///
+/// let config = ClientConfig::default();
+/// let limiter = RateLimiter::new().limit(
+///     &config.ragfair_endpoint,
+///     RateLimit {
+///         requests_per_second: 1.0,
+///         burst: 3,
+///     },
+/// );
+/// ```
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    /// Create a rate limiter without any limits.
+    pub fn new() -> Self {
+        RateLimiter::default()
+    }
+
+    /// Conservative limits for the launcher, prod, trading and ragfair endpoints of `config`.
+    pub fn for_config(config: &ClientConfig) -> Self {
+        RateLimiter::new()
+            .limit(
+                &config.launcher_endpoint,
+                RateLimit {
+                    requests_per_second: 0.5,
+                    burst: 3,
+                },
+            )
+            .limit(
+                &config.prod_endpoint,
+                RateLimit {
+                    requests_per_second: 5.0,
+                    burst: 10,
+                },
+            )
+            .limit(
+                &config.trading_endpoint,
+                RateLimit {
+                    requests_per_second: 5.0,
+                    burst: 10,
+                },
+            )
+            .limit(
+                &config.ragfair_endpoint,
+                RateLimit {
+                    requests_per_second: 1.0,
+                    burst: 5,
+                },
+            )
+    }
+
+    /// Limit requests to `endpoint`, either a host name or a base URL.
+    ///
+    /// Panics if `requests_per_second` is not a positive, finite number.
+    pub fn limit(self, endpoint: &str, limit: RateLimit) -> Self {
+        assert!(
+            limit.requests_per_second.is_finite() && limit.requests_per_second > 0.0,
+            "requests_per_second must be positive and finite, got {}",
+            limit.requests_per_second
+        );
+        self.buckets
+            .lock()
+            .unwrap()
+            .insert(host(endpoint), Bucket::new(limit));
+        self
+    }
+
+    /// Number of requests to `endpoint` currently waiting for a token.
+    pub fn queue_depth(&self, endpoint: &str) -> usize {
+        self.buckets
+            .lock()
+            .unwrap()
+            .get(&host(endpoint))
+            .map_or(0, |b| b.queued.load(Ordering::SeqCst))
+    }
+
+    /// Wait until a request to `endpoint` may be sent.
+    pub async fn acquire(&self, endpoint: &str) {
+        let host = host(endpoint);
+        let reserved = self
+            .buckets
+            .lock()
+            .unwrap()
+            .get_mut(&host)
+            .map(|b| (b.reserve(), b.queued.clone()));
+
+        if let Some((delay, queued)) = reserved {
+            if delay > Duration::from_secs(0) {
+                debug!("Rate limited request to {} for {:?}", host, delay);
+                let _guard = QueueGuard::new(queued);
+                tokio::time::delay_for(delay).await;
+            }
+        }
+    }
+}
+
+struct QueueGuard(Arc<AtomicUsize>);
+
+impl QueueGuard {
+    fn new(queued: Arc<AtomicUsize>) -> Self {
+        queued.fetch_add(1, Ordering::SeqCst);
+        QueueGuard(queued)
+    }
+}
+
+impl Drop for QueueGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn host(endpoint: &str) -> String {
+    endpoint
+        .parse::<Uri>()
+        .ok()
+        .and_then(|uri| uri.host().map(str::to_string))
+        .unwrap_or_else(|| endpoint.to_string())
+}
+
+/// Transport wrapper that waits on a `RateLimiter` before every request.
+pub(crate) struct RateLimited<T> {
+    pub(crate) inner: T,
+    pub(crate) limiter: Arc<RateLimiter>,
+}
+
+impl<T: Transport> Transport for RateLimited<T> {
+    fn send(&self, request: Request<Vec<u8>>) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let host = request.uri().host().unwrap_or_default().to_string();
+            self.limiter.acquire(&host).await;
+            self.inner.send(request).await
+        })
+    }
+}
+
+#[test]
+fn test_host() {
+    assert_eq!(
+        host("https://prod.escapefromtarkov.com"),
+        "prod.escapefromtarkov.com"
+    );
+    assert_eq!(
+        host("prod.escapefromtarkov.com"),
+        "prod.escapefromtarkov.com"
+    );
+}
+
+#[test]
+#[should_panic(expected = "requests_per_second must be positive and finite")]
+fn test_zero_rate() {
+    RateLimiter::new().limit(
+        "prod.escapefromtarkov.com",
+        RateLimit {
+            requests_per_second: 0.0,
+            burst: 1,
+        },
+    );
+}
@@ -0,0 +1,152 @@
+use crate::transport::{Transport, TransportFuture};
+use crate::{Error, Result};
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use http::{Request, Response, StatusCode};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Single API request and its response, stored as one JSONL line.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Record {
+    /// Request URL
+    pub url: String,
+    /// Request JSON body
+    pub request: Value,
+    /// Decompressed response JSON body
+    pub response: Value,
+    /// Response HTTP status
+    pub status: u16,
+}
+
+/// Transport wrapper that writes every request and response to a JSONL sink.
+///
+/// Recordings contain request bodies as-is, including login emails and password hashes.
+pub struct Recorder<T> {
+    inner: T,
+    sink: Mutex<Box<dyn Write + Send>>,
+}
+
+impl<T: Transport> Recorder<T> {
+    /// Record requests sent through `inner` to `sink`.
+    pub fn new<W: Write + Send + 'static>(inner: T, sink: W) -> Self {
+        Recorder {
+            inner,
+            sink: Mutex::new(Box::new(sink)),
+        }
+    }
+
+    /// Record requests sent through `inner` to a new file at `path`.
+    pub fn create<P: AsRef<Path>>(inner: T, path: P) -> Result<Self> {
+        Ok(Recorder::new(inner, File::create(path)?))
+    }
+}
+
+impl<T: Transport> Transport for Recorder<T> {
+    fn send(&self, request: Request<Vec<u8>>) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let url = request.uri().to_string();
+            let body = serde_json::from_slice(request.body()).unwrap_or(Value::Null);
+            let res = self.inner.send(request).await?;
+
+            let mut decode = ZlibDecoder::new(res.body().as_slice());
+            let mut response = String::new();
+            let response = match decode.read_to_string(&mut response) {
+                Ok(_) => serde_json::from_str(&response).unwrap_or(Value::String(response)),
+                Err(_) => Value::Null,
+            };
+
+            let record = Record {
+                url,
+                request: body,
+                response,
+                status: res.status().as_u16(),
+            };
+            let mut line = serde_json::to_string(&record)?;
+            line.push('\n');
+
+            let mut sink = self.sink.lock().unwrap();
+            sink.write_all(line.as_bytes())?;
+            sink.flush()?;
+
+            Ok(res)
+        })
+    }
+}
+
+/// Transport that serves responses from a recording instead of the network.
+///
+/// Each request consumes the oldest unused record with the same URL, so a session replays
+/// deterministically as long as it sends the same requests.
+#[derive(Debug)]
+pub struct Replay {
+    records: Mutex<Vec<Record>>,
+}
+
+impl Replay {
+    /// Replay a list of records.
+    pub fn new(records: Vec<Record>) -> Self {
+        Replay {
+            records: Mutex::new(records),
+        }
+    }
+
+    /// Read a JSONL recording. Blank lines are skipped.
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self> {
+        let mut records = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            records.push(serde_json::from_str(&line)?);
+        }
+
+        Ok(Replay::new(records))
+    }
+
+    /// Read a JSONL recording from a file.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Replay::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Number of records not yet replayed.
+    pub fn remaining(&self) -> usize {
+        self.records.lock().unwrap().len()
+    }
+}
+
+impl Transport for Replay {
+    fn send(&self, request: Request<Vec<u8>>) -> TransportFuture<'_> {
+        let url = request.uri().to_string();
+        let record = {
+            let mut records = self.records.lock().unwrap();
+            records
+                .iter()
+                .position(|r| r.url == url)
+                .map(|i| records.remove(i))
+        };
+
+        Box::pin(async move {
+            let record = record.ok_or_else(|| Error::Replay(url.clone()))?;
+            let request: Value = serde_json::from_slice(request.body()).unwrap_or(Value::Null);
+            if request != record.request {
+                warn!("Replayed request body for {} differs from recording", url);
+            }
+
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(serde_json::to_string(&record.response)?.as_bytes())?;
+
+            Ok(Response::builder()
+                .status(StatusCode::from_u16(record.status).unwrap_or(StatusCode::OK))
+                .body(encoder.finish()?)?)
+        })
+    }
+}
@@ -0,0 +1,107 @@
+use crate::Error;
+use http::StatusCode;
+use rand::Rng;
+use std::time::Duration;
+
+/// Retry policy for transient API failures.
+///
+/// Requests are retried with exponential backoff: the delay starts at `base_delay`, doubles after
+/// every attempt up to `max_delay` and is randomly shortened by up to `jitter` of itself.
+///
+/// ```
+/// use std::time::Duration;
+/// use tarkov::retry::RetryPolicy;
+///
+/// let policy = RetryPolicy {
+///     max_attempts: 5,
+///     base_delay: Duration::from_secs(1),
+///     ..RetryPolicy::default()
+/// };
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one. `1` disables retries.
+    pub max_attempts: u32,
+    /// Delay before the first retry.
+    pub base_delay: Duration,
+    /// Upper bound for the delay between attempts.
+    pub max_delay: Duration,
+    /// Fraction of the delay that is randomized, between `0.0` and `1.0`.
+    pub jitter: f64,
+    /// Also retry non-idempotent `items/moving` requests, such as `buy_item` or `trade_item`.
+    ///
+    /// A request that timed out may still have gone through, retrying it can buy twice.
+    pub retry_non_idempotent: bool,
+    /// Returns `true` if the error is worth retrying. Defaults to `is_transient`.
+    pub is_retryable: fn(&Error) -> bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: 0.5,
+            retry_non_idempotent: false,
+            is_retryable: is_transient,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy that never retries.
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// Delay before retrying after `attempt` failed attempts.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exp = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let delay = self
+            .base_delay
+            .checked_mul(exp)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+
+        let jitter = self.jitter.max(0.0).min(1.0) * rand::thread_rng().gen::<f64>();
+        delay.mul_f64(1.0 - jitter)
+    }
+
+    pub(crate) fn should_retry(&self, error: &Error, attempt: u32, idempotent: bool) -> bool {
+        attempt < self.max_attempts
+            && (idempotent || self.retry_non_idempotent)
+            && (self.is_retryable)(error)
+    }
+}
+
+/// Default error classification. Network errors, 5xx and 429 responses, backend errors and
+/// maintenance are considered transient.
+pub fn is_transient(error: &Error) -> bool {
+    match error {
+        Error::Io(_) | Error::Hyper(_) | Error::BackendError(_) | Error::Maintenance(_) => true,
+        Error::Status(status) => {
+            status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS
+        }
+        _ => false,
+    }
+}
+
+#[test]
+fn test_retry_delay() {
+    let policy = RetryPolicy {
+        base_delay: Duration::from_secs(1),
+        max_delay: Duration::from_secs(5),
+        jitter: 0.0,
+        ..RetryPolicy::default()
+    };
+
+    assert_eq!(policy.delay(1), Duration::from_secs(1));
+    assert_eq!(policy.delay(2), Duration::from_secs(2));
+    assert_eq!(policy.delay(3), Duration::from_secs(4));
+    assert_eq!(policy.delay(4), Duration::from_secs(5));
+    assert_eq!(policy.delay(100), Duration::from_secs(5));
+}
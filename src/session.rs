@@ -0,0 +1,220 @@
+use crate::auth;
+use crate::hwid::parse_hwid;
+use crate::profile::Notifier;
+use crate::{Error, ErrorMessage, Result, Tarkov, TarkovBuilder};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::future::Future;
+use std::io::Write;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::atomic::Ordering;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+/// Access tokens are refreshed this many seconds before they expire.
+const EXPIRY_MARGIN: u64 = 60;
+
+/// Everything needed to resume an authenticated session later.
+///
+/// Contains credentials, store it somewhere safe.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct SessionState {
+    /// Session cookie
+    pub session: String,
+    /// Hardware ID
+    pub hwid: String,
+    /// Selected profile ID
+    pub profile_id: Option<String>,
+    /// Launcher access token
+    pub access_token: Option<String>,
+    /// Launcher refresh token
+    pub refresh_token: Option<String>,
+    /// Access token expiry, in seconds since the Unix epoch
+    pub expires_at: Option<u64>,
+    /// Notifier of the selected profile
+    pub notifier: Option<Notifier>,
+}
+
+impl SessionState {
+    /// Load a session state from a JSON file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    /// Save the session state to a JSON file. On Unix, the file is only readable and writable
+    /// by its owner.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+
+        let mut file = options.open(path)?;
+        // `mode` only applies to new files.
+        #[cfg(unix)]
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        file.write_all(&serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Returns `true` if the access token expired or is about to.
+    pub fn access_token_expired(&self) -> bool {
+        match self.expires_at {
+            Some(expires_at) => unix_time() + EXPIRY_MARGIN >= expires_at,
+            None => false,
+        }
+    }
+
+    pub(crate) fn set_tokens(
+        &mut self,
+        access_token: String,
+        refresh_token: String,
+        expires_in: u64,
+    ) {
+        self.access_token = Some(access_token);
+        self.refresh_token = Some(refresh_token);
+        self.expires_at = Some(unix_time() + expires_in);
+    }
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl TarkovBuilder {
+    /// Resume a saved session.
+    ///
+    /// The session is validated with `keep_alive`. If it expired, it is renewed with the stored
+    /// tokens, see `Tarkov::renew_session`.
+    pub async fn resume(mut self, state: SessionState) -> Result<Tarkov> {
+        if state.session.is_empty() || state.hwid.is_empty() {
+            return Err(Error::InvalidParameters);
+        }
+        parse_hwid(&state.hwid)?;
+        self.wrap_transport();
+
+        let t = self.build(state);
+        match t.keep_alive().await {
+            Err(Error::NotAuthorized(_)) => {
+                info!("Saved session expired, renewing");
+                t.renew_session().await?;
+            }
+            res => res?,
+        }
+
+        Ok(t)
+    }
+}
+
+impl Tarkov {
+    /// Resume a saved session, see `TarkovBuilder::resume`.
+    pub async fn resume(state: SessionState) -> Result<Self> {
+        Tarkov::builder().resume(state).await
+    }
+
+    /// Session cookie (AKA `PHPSESSID`).
+    pub fn session(&self) -> String {
+        self.state.lock().unwrap().session.clone()
+    }
+
+    /// Hardware ID.
+    pub fn hwid(&self) -> String {
+        self.state.lock().unwrap().hwid.clone()
+    }
+
+    /// Snapshot of the current session, to be saved and resumed later.
+    pub fn session_state(&self) -> SessionState {
+        self.state.lock().unwrap().clone()
+    }
+
+    /// Exchange the stored access token for a new session and select the stored profile again.
+    /// The access token is refreshed first if it expired.
+    ///
+    /// Requests failing with `NotAuthorized` after a profile was selected do this automatically.
+    pub async fn renew_session(&self) -> Result<()> {
+        let _renewing = self.renewing.lock().await;
+        self.renew().await
+    }
+
+    /// Renew the session, unless a concurrent request already replaced `session`.
+    ///
+    /// Boxed to break the `post_json` -> `renew` -> `select_profile` -> `post_json` cycle.
+    pub(crate) fn renew_expired<'a>(
+        &'a self,
+        session: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> {
+        Box::pin(async move {
+            let _renewing = self.renewing.lock().await;
+            if self.session() != session {
+                return Ok(());
+            }
+
+            info!("Session expired, renewing");
+            self.renew().await
+        })
+    }
+
+    /// Number of successful session renewals, shared with forks.
+    pub(crate) fn renewals(&self) -> u64 {
+        self.renewals.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn can_renew(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        self.auto_renew
+            && state.profile_id.is_some()
+            && (state.access_token.is_some() || state.refresh_token.is_some())
+    }
+
+    async fn renew(&self) -> Result<()> {
+        let state = self.session_state();
+        let access_token = match (&state.access_token, &state.refresh_token) {
+            (Some(access_token), _) if !state.access_token_expired() => access_token.clone(),
+            (_, Some(refresh_token)) => {
+                info!("Refreshing access token");
+                let token =
+                    auth::refresh_token(&*self.transport, &self.config, refresh_token).await?;
+                self.state.lock().unwrap().set_tokens(
+                    token.access_token.clone(),
+                    token.refresh_token,
+                    token.expires_in,
+                );
+                token.access_token
+            }
+            _ => return Err(Error::NotAuthorized(ErrorMessage::default())),
+        };
+
+        let mut config = self.config();
+        let mut session = auth::start_game(
+            &*self.transport,
+            &mut config,
+            &access_token,
+            &state.hwid,
+            None,
+        )
+        .await?;
+        if session.queued {
+            session =
+                auth::wait_in_queue(&*self.transport, &mut config, &access_token, &state.hwid)
+                    .await?;
+        }
+        *self.game_version.lock().unwrap() = config.game_version;
+        self.state.lock().unwrap().session = session.session;
+
+        if let Some(profile_id) = &state.profile_id {
+            let mut client = self.fork();
+            client.auto_renew = false;
+            client.select_profile(profile_id).await?;
+        }
+
+        self.renewals.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
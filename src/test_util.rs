@@ -0,0 +1,75 @@
+use crate::constant;
+use crate::tree::InventoryTree;
+use std::collections::HashMap;
+
+/// Item database with a 4x3 `hideout` grid in `stash`, a foldable `rifle` with its mods, a `box`
+/// and `ammo` stacking up to 60.
+pub(crate) fn test_db() -> HashMap<String, constant::Item> {
+    let template = |id: &str, props: serde_json::Value| {
+        let item = serde_json::json!({
+            "_id": id,
+            "_name": id,
+            "_parent": "",
+            "_type": "Item",
+            "_props": props,
+        });
+        (id.to_string(), serde_json::from_value(item).unwrap())
+    };
+    let grid = serde_json::json!([{
+        "_id": "grid",
+        "_name": "hideout",
+        "_parent": "stash",
+        "_props": {
+            "filters": [],
+            "cellsH": 4,
+            "cellsV": 3,
+            "minCount": 0,
+            "maxCount": 0,
+            "maxWeight": 0
+        },
+        "_proto": "55d329c24bdc2d892f8b4567"
+    }]);
+
+    vec![
+        template("stash", serde_json::json!({ "Grids": grid })),
+        template(
+            "rifle",
+            serde_json::json!({
+                "Width": 2,
+                "Height": 1,
+                "Foldable": true,
+                "FoldedSlot": "",
+                "SizeReduceRight": 1
+            }),
+        ),
+        template("barrel", serde_json::json!({ "ExtraSizeRight": 1 })),
+        template("suppressor", serde_json::json!({ "ExtraSizeRight": 1 })),
+        template(
+            "magazine",
+            serde_json::json!({ "ExtraSizeDown": 1, "ExtraSizeForceAdd": true }),
+        ),
+        template("box", serde_json::json!({ "Width": 2, "Height": 1 })),
+        template(
+            "ammo",
+            serde_json::json!({ "Width": 1, "Height": 1, "StackMaxSize": 60 }),
+        ),
+    ]
+    .into_iter()
+    .collect()
+}
+
+/// Inventory with `items` below the `stash` root.
+pub(crate) fn test_tree(items: Vec<serde_json::Value>) -> InventoryTree {
+    let mut all = vec![serde_json::json!({ "_id": "stash", "_tpl": "stash" })];
+    all.extend(items);
+    let inventory = serde_json::json!({
+        "items": all,
+        "equipment": "equipment",
+        "stash": "stash",
+        "questRaidItems": "quest",
+        "questStashItems": "quest_stash",
+        "fastPanel": {},
+    });
+
+    InventoryTree::new(&serde_json::from_value(inventory).unwrap())
+}
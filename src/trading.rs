@@ -1,10 +1,6 @@
-use crate::inventory::{
-    BarterItem, InventoryUpdate, Item, MoveItemRequest, RagfairResponseData, Upd,
-};
-use crate::{
-    handle_error, handle_error2, Error, ErrorResponse, Result, Tarkov, PROD_ENDPOINT,
-    TRADING_ENDPOINT,
-};
+use crate::batch::ActionBatch;
+use crate::inventory::{BarterItem, InventoryUpdate, Item, Upd};
+use crate::{handle_error, Error, ErrorResponse, Result, Tarkov};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -17,6 +13,41 @@ pub enum TradingError {
     /// Loyalty level is not high enough to purchase this item.
     #[error(display = "bad loyalty level")]
     BadLoyaltyLevel,
+    /// Trader does not have enough money to buy the item.
+    #[error(display = "trader is out of money")]
+    TraderOutOfMoney,
+    /// Item price changed since the trade was requested.
+    #[error(display = "price changed")]
+    PriceChanged,
+    /// Trader is not available.
+    #[error(display = "trader is disabled")]
+    TraderDisabled,
+    /// Item was already sold.
+    #[error(display = "item has been sold")]
+    ItemSold,
+    /// Not enough space in the stash for the money received.
+    #[error(display = "not enough space for money")]
+    NotEnoughSpaceForMoney,
+    /// Unspecified trading failure, see the error message.
+    #[error(display = "trading failed")]
+    Failed,
+}
+
+impl TradingError {
+    /// Map a backend error code of the trading API.
+    pub(crate) fn from_code(code: u64) -> Option<Self> {
+        match code {
+            500 => Some(TradingError::Failed),
+            505 => Some(TradingError::TraderOutOfMoney),
+            509 => Some(TradingError::PriceChanged),
+            512 => Some(TradingError::TraderDisabled),
+            513 => Some(TradingError::ItemSold),
+            518 => Some(TradingError::NotEnoughSpaceForMoney),
+            1510 => Some(TradingError::BadLoyaltyLevel),
+            1514 => Some(TradingError::TransactionError),
+            _ => None,
+        }
+    }
 }
 
 /// Trader info
@@ -207,54 +238,44 @@ pub struct TraderItem {
 }
 
 #[derive(Debug, Serialize)]
-struct TradeItemRequest<'a> {
+pub(crate) struct TradeItemRequest {
     #[serde(rename = "Action")]
-    action: &'a str,
+    pub(crate) action: &'static str,
     #[serde(rename = "type")]
-    trade_type: &'a str,
+    pub(crate) trade_type: &'static str,
     #[serde(rename = "tid")]
-    trader_id: &'a str,
-    item_id: &'a str,
-    count: u64,
-    scheme_id: u64,
-    scheme_items: &'a [BarterItem],
-}
-
-#[derive(Debug, Deserialize)]
-struct TradeResponse {
-    #[serde(flatten)]
-    error: ErrorResponse,
-    data: serde_json::Value,
+    pub(crate) trader_id: String,
+    pub(crate) item_id: String,
+    pub(crate) count: u64,
+    pub(crate) scheme_id: u64,
+    pub(crate) scheme_items: Vec<BarterItem>,
 }
 
 #[derive(Debug, Serialize)]
-struct SellItemRequest<'a> {
+pub(crate) struct SellItemRequest {
     #[serde(rename = "Action")]
-    action: &'a str,
+    pub(crate) action: &'static str,
     #[serde(rename = "type")]
-    trade_type: &'a str,
+    pub(crate) trade_type: &'static str,
     #[serde(rename = "tid")]
-    trader_id: &'a str,
-    items: &'a [SellItem],
+    pub(crate) trader_id: String,
+    pub(crate) items: Vec<SellItem>,
 }
 
 #[derive(Debug, Serialize)]
-struct SellItem {
-    id: String,
-    count: u64,
-    scheme_id: u64,
-}
-
-#[derive(Debug, Deserialize)]
-struct SellResponse {
-    #[serde(flatten)]
-    error: ErrorResponse,
+pub(crate) struct SellItem {
+    pub(crate) id: String,
+    pub(crate) count: u64,
+    pub(crate) scheme_id: u64,
 }
 
 impl Tarkov {
     /// Get a list of all traders.
     pub async fn get_traders(&self) -> Result<Vec<Trader>> {
-        let url = format!("{}/client/trading/api/getTradersList", TRADING_ENDPOINT);
+        let url = format!(
+            "{}/client/trading/api/getTradersList",
+            self.config.trading_endpoint
+        );
         let res: TradersResponse = self.post_json(&url, &{}).await?;
 
         handle_error(res.error, res.data)
@@ -268,7 +289,7 @@ impl Tarkov {
 
         let url = format!(
             "{}/client/trading/api/getTrader/{}",
-            TRADING_ENDPOINT, trader_id
+            self.config.trading_endpoint, trader_id
         );
         let res: TraderResponse = self.post_json(&url, &{}).await?;
 
@@ -278,7 +299,7 @@ impl Tarkov {
     async fn get_trader_items_raw(&self, trader_id: &str) -> Result<TraderItems> {
         let url = format!(
             "{}/client/trading/api/getTraderAssort/{}",
-            TRADING_ENDPOINT, trader_id
+            self.config.trading_endpoint, trader_id
         );
         let res: TraderItemsResponse = self.post_json(&url, &{}).await?;
 
@@ -291,7 +312,7 @@ impl Tarkov {
     ) -> Result<HashMap<String, Vec<Vec<Price>>>> {
         let url = format!(
             "{}/client/trading/api/getUserAssortPrice/trader/{}",
-            TRADING_ENDPOINT, trader_id
+            self.config.trading_endpoint, trader_id
         );
         let res: TraderPricesResponse = self.post_json(&url, &{}).await?;
 
@@ -315,27 +336,26 @@ impl Tarkov {
                 continue;
             }
 
-            let loyalty_level = items
-                .loyal_level_items
+            let loyalty_level = match items.loyal_level_items.get(&item.id) {
+                Some(loyalty_level) => *loyalty_level,
+                None => return Err(Error::MissingLoyaltyLevel(trader_id.to_string(), item.id)),
+            };
+            let price = items
+                .barter_scheme
                 .get(&item.id)
-                .expect("Loyalty level could not be mapped.");
-            let price = {
-                let barter_or_price = match items.barter_scheme.get(&item.id) {
-                    None => prices
-                        .get(&item.id)
-                        .expect("Item price could not be mapped."),
-                    Some(barter) => barter,
-                };
-
-                barter_or_price.get(0)
+                .or_else(|| prices.get(&item.id))
+                .and_then(|price| price.first());
+            let price = match price {
+                Some(price) => price.clone(),
+                None => return Err(Error::MissingPrice(trader_id.to_string(), item.id)),
             };
 
             let trader_item = TraderItem {
                 id: item.id,
                 schema_id: item.schema_id,
                 upd: item.upd,
-                price: price.expect("Item price could not be mapped.").clone(),
-                loyalty_level: *loyalty_level,
+                price,
+                loyalty_level,
             };
 
             result.push(trader_item);
@@ -359,31 +379,9 @@ impl Tarkov {
             return Err(Error::InvalidParameters);
         }
 
-        let url = format!("{}/client/game/profile/items/moving", PROD_ENDPOINT);
-        let body = MoveItemRequest {
-            data: &[TradeItemRequest {
-                action: "TradingConfirm",
-                trade_type: "buy_from_trader",
-                trader_id,
-                item_id,
-                count: quantity,
-                scheme_id: 0,
-                scheme_items: barter_items,
-            }],
-            tm: 0,
-        };
-
-        let res: TradeResponse = self.post_json(&url, &body).await?;
-        handle_error2(res.error)?;
-
-        let res: RagfairResponseData = Deserialize::deserialize(res.data)?;
-        if !res.errors.is_empty() {
-            let error = &res.errors[0];
-            return Err(Error::UnknownAPIError(error.code));
-        }
-
-        let items: InventoryUpdate = Deserialize::deserialize(res.items)?;
-        Ok(items)
+        let mut batch = ActionBatch::new();
+        batch.trade(trader_id, item_id, quantity, barter_items);
+        self.send_single(batch).await
     }
 
     /// Sell items to trader.
@@ -397,31 +395,8 @@ impl Tarkov {
             return Err(Error::InvalidParameters);
         }
 
-        let url = format!("{}/client/game/profile/items/moving", PROD_ENDPOINT);
-        let body = MoveItemRequest {
-            data: &[SellItemRequest {
-                action: "TradingConfirm",
-                trade_type: "sell_to_trader",
-                trader_id,
-                items: &[SellItem {
-                    id: item_id.to_string(),
-                    count: quantity,
-                    scheme_id: 0,
-                }],
-            }],
-            tm: 0,
-        };
-
-        let res: TradeResponse = self.post_json(&url, &body).await?;
-        handle_error2(res.error)?;
-
-        let res: RagfairResponseData = Deserialize::deserialize(res.data)?;
-        if !res.errors.is_empty() {
-            let error = &res.errors[0];
-            return Err(Error::UnknownAPIError(error.code));
-        }
-
-        let items: InventoryUpdate = Deserialize::deserialize(res.items)?;
-        Ok(items)
+        let mut batch = ActionBatch::new();
+        batch.sell(trader_id, item_id, quantity);
+        self.send_single(batch).await
     }
 }
@@ -0,0 +1,72 @@
+use crate::Result;
+use http::{Request, Response};
+use hyper::client::connect::dns::GaiResolver;
+use hyper::client::{Client, HttpConnector};
+use hyper::Body;
+use hyper_tls::HttpsConnector;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Future returned by `Transport::send`.
+pub type TransportFuture<'a> = Pin<Box<dyn Future<Output = Result<Response<Vec<u8>>>> + Send + 'a>>;
+
+/// HTTP transport used to send requests to the EFT API.
+///
+/// Implement this trait to route requests through a proxy, a different HTTP stack or a local
+/// stub server. Response bodies are returned untouched, the EFT API compresses them with zlib.
+pub trait Transport: Send + Sync {
+    /// Send a request and return the raw response.
+    fn send(&self, request: Request<Vec<u8>>) -> TransportFuture<'_>;
+}
+
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+    fn send(&self, request: Request<Vec<u8>>) -> TransportFuture<'_> {
+        (**self).send(request)
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    fn send(&self, request: Request<Vec<u8>>) -> TransportFuture<'_> {
+        (**self).send(request)
+    }
+}
+
+/// Default transport using `hyper` with `hyper-tls`.
+#[derive(Debug, Clone)]
+pub struct HyperTransport {
+    client: Client<HttpsConnector<HttpConnector<GaiResolver>>, Body>,
+}
+
+impl HyperTransport {
+    /// Create a new HTTPS transport.
+    pub fn new() -> Self {
+        let https = HttpsConnector::new();
+        let client = Client::builder().build::<_, Body>(https);
+
+        HyperTransport { client }
+    }
+}
+
+impl Default for HyperTransport {
+    fn default() -> Self {
+        HyperTransport::new()
+    }
+}
+
+impl Transport for HyperTransport {
+    fn send(&self, request: Request<Vec<u8>>) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let (parts, body) = request.into_parts();
+            let res = self
+                .client
+                .request(Request::from_parts(parts, Body::from(body)))
+                .await?;
+
+            let (parts, body) = res.into_parts();
+            let body = hyper::body::to_bytes(body).await?;
+
+            Ok(Response::from_parts(parts, body.to_vec()))
+        })
+    }
+}
@@ -0,0 +1,382 @@
+use crate::inventory::{InventoryUpdate, Item};
+use crate::profile::Inventory;
+use std::collections::{HashMap, HashSet};
+
+/// Inventory update conflicting with the local inventory.
+#[derive(Debug, Clone, PartialEq, err_derive::Error)]
+pub enum UpdateConflict {
+    /// Changed or deleted item is not in the inventory.
+    #[error(display = "unknown item {}", _0)]
+    UnknownItem(String),
+    /// New item is already in the inventory.
+    #[error(display = "duplicate item {}", _0)]
+    DuplicateItem(String),
+}
+
+/// Top-level container of an inventory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InventoryRoot {
+    /// Equipped items.
+    Equipment,
+    /// Stash.
+    Stash,
+    /// Quest items found in raid.
+    QuestRaidItems,
+    /// Quest item stash.
+    QuestStashItems,
+}
+
+/// Inventory items linked by `parent_id` and `slot_id`.
+///
+/// ```no_run
+/// # async fn run(t: tarkov::Tarkov) -> tarkov::Result<()> {
+/// use tarkov::tree::{InventoryRoot, InventoryTree};
+///
+/// let profile = t.get_profiles().await?.remove(0);
+/// let tree = InventoryTree::new(&profile.inventory);
+/// for item in tree.children_in(tree.root_id(InventoryRoot::Stash).unwrap(), "hideout") {
+///     println!("{}: {} items inside", item.schema_id, tree.descendants(&item.id).len());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct InventoryTree {
+    items: HashMap<String, Item>,
+    children: HashMap<String, Vec<String>>,
+    roots: HashMap<InventoryRoot, String>,
+}
+
+impl InventoryTree {
+    /// Build the tree of a profile inventory.
+    pub fn new(inventory: &Inventory) -> Self {
+        let mut roots = HashMap::new();
+        roots.insert(InventoryRoot::Equipment, inventory.equipment.clone());
+        if let Some(stash) = &inventory.stash {
+            roots.insert(InventoryRoot::Stash, stash.clone());
+        }
+        roots.insert(
+            InventoryRoot::QuestRaidItems,
+            inventory.quest_raid_items.clone(),
+        );
+        roots.insert(
+            InventoryRoot::QuestStashItems,
+            inventory.quest_stash_items.clone(),
+        );
+
+        let mut tree = InventoryTree {
+            items: HashMap::new(),
+            children: HashMap::new(),
+            roots,
+        };
+        for item in &inventory.items {
+            tree.insert(item.clone());
+        }
+
+        tree
+    }
+
+    /// Number of items.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns `true` if the tree has no items.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Item by ID.
+    pub fn get(&self, id: &str) -> Option<&Item> {
+        self.items.get(id)
+    }
+
+    /// Every item, in no particular order.
+    pub fn items(&self) -> impl Iterator<Item = &Item> {
+        self.items.values()
+    }
+
+    /// ID of a top-level container.
+    pub fn root_id(&self, root: InventoryRoot) -> Option<&str> {
+        self.roots.get(&root).map(|id| id.as_str())
+    }
+
+    /// Top-level container holding `id`, or the container itself.
+    pub fn root(&self, id: &str) -> Option<InventoryRoot> {
+        let top = match self.ancestors(id).last() {
+            Some(item) => item.id.as_str(),
+            None => id,
+        };
+
+        self.roots
+            .iter()
+            .find(|(_, root_id)| *root_id == top)
+            .map(|(root, _)| *root)
+    }
+
+    /// Item holding `id`.
+    pub fn parent(&self, id: &str) -> Option<&Item> {
+        let parent_id = self.items.get(id)?.parent_id.as_ref()?;
+        self.items.get(parent_id)
+    }
+
+    /// Items holding `id`, from its parent up to the top-level container.
+    pub fn ancestors(&self, id: &str) -> Vec<&Item> {
+        let mut ancestors: Vec<&Item> = Vec::new();
+        let mut current = id;
+        while let Some(parent) = self.parent(current) {
+            // Malformed inventories may link items in a loop.
+            if parent.id == id || ancestors.iter().any(|a| a.id == parent.id) {
+                break;
+            }
+            ancestors.push(parent);
+            current = &parent.id;
+        }
+
+        ancestors
+    }
+
+    /// Items directly inside `id`, in inventory order.
+    pub fn children(&self, id: &str) -> Vec<&Item> {
+        self.children
+            .get(id)
+            .map(|children| children.iter().filter_map(|c| self.items.get(c)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Items directly inside the `slot_id` slot or grid of `id`, e.g. `hideout` for the stash or
+    /// `main` for a backpack.
+    pub fn children_in(&self, id: &str, slot_id: &str) -> Vec<&Item> {
+        self.children(id)
+            .into_iter()
+            .filter(|item| item.slot_id.as_deref() == Some(slot_id))
+            .collect()
+    }
+
+    /// Every item inside `id`, at any depth. Parents come before their children.
+    pub fn descendants(&self, id: &str) -> Vec<&Item> {
+        let mut descendants = Vec::new();
+        let mut visited = HashSet::new();
+        visited.insert(id);
+
+        let mut stack: Vec<&Item> = self.children(id).into_iter().rev().collect();
+        while let Some(item) = stack.pop() {
+            if !visited.insert(&item.id) {
+                continue;
+            }
+            descendants.push(item);
+            stack.extend(self.children(&item.id).into_iter().rev());
+        }
+
+        descendants
+    }
+
+    /// Returns `true` if `item_id` is inside `container_id`, at any depth.
+    pub fn contains(&self, container_id: &str, item_id: &str) -> bool {
+        self.ancestors(item_id)
+            .iter()
+            .any(|item| item.id == container_id)
+    }
+
+    /// Copy of `id` and every item inside it, e.g. a weapon with all its mods. The item itself
+    /// comes first.
+    pub fn subtree(&self, id: &str) -> Vec<Item> {
+        let item = match self.items.get(id) {
+            Some(item) => item,
+            None => return Vec::new(),
+        };
+
+        let mut subtree = vec![item.clone()];
+        subtree.extend(self.descendants(id).into_iter().cloned());
+        subtree
+    }
+
+    /// Apply the changes returned by trading, flea market and item moving requests.
+    ///
+    /// New items are added, changed items are replaced and deleted items are removed with
+    /// everything inside them. Nothing is applied if the update conflicts with the inventory.
+    pub fn apply(&mut self, update: &InventoryUpdate) -> Result<(), UpdateConflict> {
+        let new = update.new.as_deref().unwrap_or_default();
+        let change = update.change.as_deref().unwrap_or_default();
+        let del = update.del.as_deref().unwrap_or_default();
+
+        let mut added = HashSet::new();
+        for item in new {
+            if self.items.contains_key(&item.id) || !added.insert(&item.id) {
+                return Err(UpdateConflict::DuplicateItem(item.id.clone()));
+            }
+        }
+        let known = |id: &String| self.items.contains_key(id) || added.contains(id);
+        if let Some(item) = change.iter().find(|item| !known(&item.id)) {
+            return Err(UpdateConflict::UnknownItem(item.id.clone()));
+        }
+        if let Some(item) = del.iter().find(|item| !known(&item.id)) {
+            return Err(UpdateConflict::UnknownItem(item.id.clone()));
+        }
+
+        for item in new {
+            self.insert(item.clone());
+        }
+        for item in change {
+            self.unlink(&item.id);
+            self.insert(item.clone());
+        }
+        for item in del {
+            let mut removed: Vec<String> = self
+                .descendants(&item.id)
+                .into_iter()
+                .map(|d| d.id.clone())
+                .collect();
+            removed.push(item.id.clone());
+            for id in removed {
+                self.unlink(&id);
+                self.items.remove(&id);
+                self.children.remove(&id);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn insert(&mut self, item: Item) {
+        if let Some(parent_id) = &item.parent_id {
+            self.children
+                .entry(parent_id.clone())
+                .or_default()
+                .push(item.id.clone());
+        }
+        self.items.insert(item.id.clone(), item);
+    }
+
+    /// Remove `id` from the children of its parent.
+    fn unlink(&mut self, id: &str) {
+        let parent_id = match self.items.get(id).and_then(|item| item.parent_id.as_ref()) {
+            Some(parent_id) => parent_id,
+            None => return,
+        };
+        if let Some(children) = self.children.get_mut(parent_id) {
+            children.retain(|c| c != id);
+        }
+    }
+}
+
+#[cfg(test)]
+fn test_item(id: &str, parent_id: Option<&str>, slot_id: &str) -> Item {
+    Item {
+        id: id.to_string(),
+        schema_id: "5447a9cd4bdc2dbd208b4567".to_string(),
+        parent_id: parent_id.map(|p| p.to_string()),
+        slot_id: Some(slot_id.to_string()),
+        upd: None,
+        location: None,
+    }
+}
+
+#[cfg(test)]
+fn test_inventory() -> Inventory {
+    Inventory {
+        items: vec![
+            test_item("equipment", None, ""),
+            test_item("stash", None, ""),
+            test_item("quest", None, ""),
+            test_item("quest_stash", None, ""),
+            test_item("backpack", Some("equipment"), "Backpack"),
+            test_item("ammo", Some("backpack"), "main"),
+            test_item("rifle", Some("stash"), "hideout"),
+            test_item("receiver", Some("rifle"), "mod_reciever"),
+            test_item("scope", Some("receiver"), "mod_scope"),
+            test_item("magazine", Some("rifle"), "mod_magazine"),
+            test_item("orphan", Some("missing"), "hideout"),
+        ],
+        equipment: "equipment".to_string(),
+        stash: Some("stash".to_string()),
+        quest_raid_items: "quest".to_string(),
+        quest_stash_items: "quest_stash".to_string(),
+        fast_panel: serde_json::Value::Null,
+    }
+}
+
+#[test]
+fn test_inventory_tree() {
+    let tree = InventoryTree::new(&test_inventory());
+    assert_eq!(tree.len(), 11);
+    assert_eq!(tree.root("scope"), Some(InventoryRoot::Stash));
+    assert_eq!(tree.root("ammo"), Some(InventoryRoot::Equipment));
+    assert_eq!(tree.root("quest"), Some(InventoryRoot::QuestRaidItems));
+    assert_eq!(tree.root("orphan"), None);
+
+    assert_eq!(tree.parent("ammo").unwrap().id, "backpack");
+    let ancestors: Vec<&str> = tree.ancestors("scope").iter().map(|i| &*i.id).collect();
+    assert_eq!(ancestors, ["receiver", "rifle", "stash"]);
+
+    assert!(tree.contains("equipment", "ammo"));
+    assert!(!tree.contains("stash", "ammo"));
+    assert_eq!(tree.children_in("stash", "hideout").len(), 1);
+    assert_eq!(tree.children_in("rifle", "mod_magazine")[0].id, "magazine");
+}
+
+#[test]
+fn test_inventory_subtree() {
+    let tree = InventoryTree::new(&test_inventory());
+    let ids: Vec<String> = tree.subtree("rifle").into_iter().map(|i| i.id).collect();
+    assert_eq!(ids, ["rifle", "receiver", "scope", "magazine"]);
+    assert!(tree.subtree("missing").is_empty());
+}
+
+#[test]
+fn test_inventory_tree_cycle() {
+    let mut inventory = test_inventory();
+    inventory.items.push(test_item("a", Some("b"), "main"));
+    inventory.items.push(test_item("b", Some("a"), "main"));
+
+    let tree = InventoryTree::new(&inventory);
+    assert_eq!(tree.ancestors("a").len(), 1);
+    assert_eq!(tree.descendants("a").len(), 1);
+    assert_eq!(tree.root("a"), None);
+}
+
+#[test]
+fn test_inventory_tree_apply() {
+    use crate::inventory::DeletedItem;
+
+    let mut tree = InventoryTree::new(&test_inventory());
+    let update = InventoryUpdate {
+        new: Some(vec![test_item("bought", Some("stash"), "hideout")]),
+        change: Some(vec![test_item("ammo", Some("stash"), "hideout")]),
+        del: Some(vec![DeletedItem {
+            id: "rifle".to_string(),
+        }]),
+    };
+    tree.apply(&update).unwrap();
+
+    assert_eq!(tree.len(), 8);
+    assert!(tree.get("scope").is_none());
+    assert!(tree.children("backpack").is_empty());
+    let stash: Vec<&str> = tree.children("stash").iter().map(|i| &*i.id).collect();
+    assert_eq!(stash, ["bought", "ammo"]);
+}
+
+#[test]
+fn test_inventory_tree_apply_conflict() {
+    let mut tree = InventoryTree::new(&test_inventory());
+    let update = InventoryUpdate {
+        new: Some(vec![test_item("bought", Some("stash"), "hideout")]),
+        change: Some(vec![test_item("missing", Some("stash"), "hideout")]),
+        del: None,
+    };
+    assert_eq!(
+        tree.apply(&update),
+        Err(UpdateConflict::UnknownItem("missing".to_string()))
+    );
+    assert!(tree.get("bought").is_none());
+
+    let update = InventoryUpdate {
+        new: Some(vec![test_item("rifle", Some("stash"), "hideout")]),
+        change: None,
+        del: None,
+    };
+    assert_eq!(
+        tree.apply(&update),
+        Err(UpdateConflict::DuplicateItem("rifle".to_string()))
+    );
+}
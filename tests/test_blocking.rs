@@ -0,0 +1,57 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tarkov::blocking;
+use tarkov::hwid::generate_hwid;
+use tarkov::market_filter::MarketFilter;
+use tarkov::mock::{MockServer, MOCK_OFFER_ID, MOCK_PROFILE_ID, MOCK_SESSION, MOCK_TRADER_ID};
+use tarkov::{Error, Result, Tarkov};
+
+fn login(server: &Arc<MockServer>) -> Result<blocking::Tarkov> {
+    blocking::Tarkov::connect(Tarkov::builder().transport(server.clone()).login(
+        "me@example.com",
+        "password",
+        &generate_hwid(),
+    ))
+}
+
+#[test]
+fn test_blocking_login() -> Result<()> {
+    let server = Arc::new(MockServer::new());
+    let t = login(&server)?;
+    assert_eq!(t.session(), MOCK_SESSION);
+
+    t.select_profile(MOCK_PROFILE_ID)?;
+    assert_eq!(
+        t.session_state().profile_id.as_deref(),
+        Some(MOCK_PROFILE_ID)
+    );
+
+    server.fail("/launcher/login", 206, "bad login");
+    match login(&server) {
+        Err(Error::LoginError(_, _)) => {}
+        _ => panic!("expected login error"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_blocking_requests() -> Result<()> {
+    let server = Arc::new(MockServer::new());
+    let mut t = login(&server)?;
+
+    assert_eq!(t.get_trader_items(MOCK_TRADER_ID)?.len(), 2);
+    let offers = t.search_market(0, 15, MarketFilter::default())?;
+    assert_eq!(offers.offers[0].id, MOCK_OFFER_ID);
+
+    let mut errors = t.spawn_keep_alive(Duration::from_millis(50));
+    std::thread::sleep(Duration::from_millis(200));
+    assert!(errors.try_recv().is_err());
+    assert!(server
+        .requests()
+        .iter()
+        .any(|r| r.url.contains("/client/game/keepalive")));
+    t.stop_keep_alive();
+
+    Ok(())
+}
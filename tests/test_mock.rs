@@ -0,0 +1,1053 @@
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tarkov::auth::LoginError;
+use tarkov::batch::ActionBatch;
+use tarkov::hwid::{generate_hwid, HwidError};
+use tarkov::inventory::{BarterItem, Container, EquipmentSlot, Location};
+use tarkov::login::{LoginState, QueueUpdate};
+use tarkov::market_filter::MarketFilter;
+use tarkov::mock::{
+    MockServer, MOCK_ACCESS_TOKEN, MOCK_OFFER_ID, MOCK_PROFILE_ID, MOCK_REFRESH_TOKEN,
+    MOCK_SESSION, MOCK_TRADER_ID,
+};
+use tarkov::pool::TarkovPool;
+use tarkov::profile::Side;
+use tarkov::ragfair::{RagfairError, Requirement};
+use tarkov::rate_limit::{RateLimit, RateLimiter};
+use tarkov::record::Replay;
+use tarkov::retry::RetryPolicy;
+use tarkov::session::SessionState;
+use tarkov::trading::TradingError;
+use tarkov::{Error, Result, Tarkov};
+
+fn mock_session(server: &Arc<MockServer>) -> Tarkov {
+    Tarkov::builder()
+        .transport(server.clone())
+        .from_session(MOCK_SESSION)
+}
+
+#[tokio::test]
+async fn test_mock_login() -> Result<()> {
+    let server = Arc::new(MockServer::new());
+    let t = Tarkov::builder()
+        .transport(server.clone())
+        .login("me@example.com", "password", &generate_hwid())
+        .await?;
+    assert_eq!(t.session(), MOCK_SESSION);
+
+    let t = Tarkov::builder()
+        .transport(server.clone())
+        .from_access_token(MOCK_ACCESS_TOKEN, &generate_hwid())
+        .await?;
+    assert_eq!(t.session(), MOCK_SESSION);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 3);
+    assert!(requests[0].url.contains("/launcher/login"));
+    assert_eq!(
+        requests[0].body["pass"],
+        format!("{:x}", md5::compute("password"))
+    );
+    assert!(requests[1].url.contains("/launcher/game/start"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_login_errors() {
+    let server = Arc::new(MockServer::new());
+    server.fail("/launcher/login", 214, "captcha required");
+
+    let res = Tarkov::builder()
+        .transport(server.clone())
+        .login("me@example.com", "password", &generate_hwid())
+        .await;
+    match res {
+        Err(Error::LoginError(LoginError::CaptchaRequired, _)) => {}
+        _ => panic!("expected captcha error"),
+    }
+
+    let sent = server.requests().len();
+    let res = Tarkov::builder()
+        .transport(server.clone())
+        .login("me@example.com", "password", "hwid")
+        .await;
+    match res {
+        Err(Error::HwidError(HwidError::MissingPrefix)) => {}
+        _ => panic!("expected hwid error"),
+    }
+    assert_eq!(server.requests().len(), sent);
+}
+
+#[tokio::test]
+async fn test_mock_login_flow() -> Result<()> {
+    let server = Arc::new(MockServer::new());
+    server
+        .fail_once("/launcher/login", 214, "captcha required")
+        .fail_once("/launcher/login", 209, "2fa required");
+
+    let mut flow = Tarkov::builder().transport(server.clone()).login_flow(
+        "me@example.com",
+        "password",
+        &generate_hwid(),
+    );
+    assert_eq!(flow.submit().await?, LoginState::CaptchaRequired);
+    match flow.two_factor("XYZ").await {
+        Err(Error::UnexpectedLoginStep(LoginState::CaptchaRequired)) => {}
+        _ => panic!("expected unexpected step"),
+    }
+    assert_eq!(
+        flow.captcha("captcha").await?,
+        LoginState::TwoFactorRequired
+    );
+
+    server.fail_once("/launcher/hardwareCode/activate", 211, "bad code");
+    match flow.two_factor("ABC").await {
+        Err(Error::LoginError(LoginError::BadTwoFactorCode, _)) => {}
+        _ => panic!("expected bad 2fa code"),
+    }
+    assert_eq!(flow.state(), LoginState::TwoFactorRequired);
+
+    server.respond(
+        "/launcher/game/start",
+        serde_json::json!({ "queued": true, "session": MOCK_SESSION }),
+    );
+    assert_eq!(flow.two_factor("XYZ").await?, LoginState::Queued);
+    assert_eq!(flow.poll().await?, LoginState::Queued);
+    server.respond(
+        "/launcher/game/start",
+        serde_json::json!({ "queued": false, "session": MOCK_SESSION }),
+    );
+    assert_eq!(flow.poll().await?, LoginState::LoggedIn);
+
+    let t = flow.finish()?;
+    assert_eq!(t.session(), MOCK_SESSION);
+
+    let requests = server.requests();
+    let paths: Vec<_> = requests
+        .iter()
+        .map(|r| {
+            r.url
+                .split('?')
+                .next()
+                .unwrap()
+                .rsplit(".com")
+                .next()
+                .unwrap()
+        })
+        .collect();
+    assert_eq!(
+        paths,
+        [
+            "/launcher/login",
+            "/launcher/login",
+            "/launcher/hardwareCode/activate",
+            "/launcher/hardwareCode/activate",
+            "/launcher/login",
+            "/launcher/game/start",
+            "/launcher/queue/status",
+            "/launcher/game/start",
+            "/launcher/queue/status",
+            "/launcher/game/start",
+        ]
+    );
+    assert_eq!(requests[1].body["captcha"], "captcha");
+    assert_eq!(requests[3].body["activateCode"], "XYZ");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_login_queue() -> Result<()> {
+    let server = Arc::new(MockServer::new());
+    server.respond(
+        "/launcher/game/start",
+        serde_json::json!({ "queued": true, "session": MOCK_SESSION }),
+    );
+
+    let mut flow = Tarkov::builder().transport(server.clone()).login_flow(
+        "me@example.com",
+        "password",
+        &generate_hwid(),
+    );
+    assert_eq!(flow.submit().await?, LoginState::Queued);
+
+    server.respond(
+        "/launcher/queue/status",
+        serde_json::json!({ "queued": true, "position": 3, "waitTime": 0 }),
+    );
+    let mut updates = flow.wait_queue();
+    match updates.recv().await {
+        Some(Ok(QueueUpdate::Waiting(status))) => assert_eq!(status.position, 3),
+        _ => panic!("expected queue position"),
+    }
+
+    server
+        .respond(
+            "/launcher/queue/status",
+            serde_json::json!({ "queued": false, "position": 0, "waitTime": 0 }),
+        )
+        .respond(
+            "/launcher/game/start",
+            serde_json::json!({ "queued": false, "session": MOCK_SESSION }),
+        );
+    match updates.recv().await {
+        Some(Ok(QueueUpdate::Ready(t))) => assert_eq!(t.session(), MOCK_SESSION),
+        _ => panic!("expected session"),
+    }
+    assert!(updates.recv().await.is_none());
+
+    // Plain login waits in the queue.
+    server.respond(
+        "/launcher/game/start",
+        serde_json::json!({ "queued": true, "session": "queued-session" }),
+    );
+    let hwid = generate_hwid();
+    let login = Tarkov::builder()
+        .transport(server.clone())
+        .from_access_token(MOCK_ACCESS_TOKEN, &hwid);
+    let ready = async {
+        tokio::time::delay_for(Duration::from_millis(100)).await;
+        server.respond(
+            "/launcher/game/start",
+            serde_json::json!({ "queued": false, "session": MOCK_SESSION }),
+        );
+    };
+    let (t, _) = tokio::join!(login, ready);
+    assert_eq!(t?.session(), MOCK_SESSION);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_game_version_discovery() -> Result<()> {
+    let server = Arc::new(MockServer::new());
+    server.set_game_version("0.12.8.9100");
+
+    let t = Tarkov::builder()
+        .transport(server.clone())
+        .login("me@example.com", "password", &generate_hwid())
+        .await?;
+    assert_eq!(t.config().game_version, "0.12.8.9100");
+    assert_eq!(server.requests().len(), 3);
+
+    let t = Tarkov::builder()
+        .transport(server.clone())
+        .from_access_token(MOCK_ACCESS_TOKEN, &generate_hwid())
+        .await?;
+    assert_eq!(t.config().game_version, "0.12.8.9100");
+    assert!(server.requests()[4].url.contains("/launcher/GetDistrib"));
+
+    // Renewing an expired session falls back the same way.
+    let t = Tarkov::builder()
+        .transport(server.clone())
+        .resume(SessionState {
+            session: MOCK_SESSION.to_string(),
+            hwid: generate_hwid(),
+            profile_id: Some(MOCK_PROFILE_ID.to_string()),
+            access_token: Some(MOCK_ACCESS_TOKEN.to_string()),
+            ..SessionState::default()
+        })
+        .await?;
+    server.fail_once("/client/game/profile/list", 201, "not authorized");
+    let count = server.requests().len();
+    t.get_profiles().await?;
+    assert!(server.requests()[count..]
+        .iter()
+        .any(|r| r.url.contains("/launcher/GetDistrib")));
+
+    // A version found while renewing is kept for the client and later renewals.
+    let server = Arc::new(MockServer::new());
+    let t = Tarkov::builder()
+        .transport(server.clone())
+        .login("me@example.com", "password", &generate_hwid())
+        .await?;
+    t.select_profile(MOCK_PROFILE_ID).await?;
+    server.set_game_version("0.12.8.9200");
+    server.fail_once("/client/game/profile/list", 201, "not authorized");
+    t.get_profiles().await?;
+    assert_eq!(t.config().game_version, "0.12.8.9200");
+
+    server.fail_once("/client/game/profile/list", 201, "not authorized");
+    let count = server.requests().len();
+    t.get_profiles().await?;
+    let renewal: Vec<_> = server.requests()[count..]
+        .iter()
+        .map(|r| r.url.clone())
+        .collect();
+    assert_eq!(
+        renewal.iter().filter(|u| u.contains("/game/start")).count(),
+        1
+    );
+    assert!(!renewal.iter().any(|u| u.contains("/launcher/GetDistrib")));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_error_messages() -> Result<()> {
+    let server = Arc::new(MockServer::new());
+    let t = mock_session(&server);
+
+    server.fail_once("/client/game/keepalive", 263, "Server is on maintenance");
+    let e = t.keep_alive().await.unwrap_err();
+    match &e {
+        Error::Maintenance(_) => {}
+        _ => panic!("expected maintenance"),
+    }
+    assert_eq!(e.message(), Some("Server is on maintenance"));
+    assert_eq!(
+        e.to_string(),
+        "api is down for maintenance: Server is on maintenance"
+    );
+
+    server.fail_once("/client/game/keepalive", 9999, "");
+    match t.keep_alive().await {
+        Err(Error::UnknownAPIError(9999, m)) => assert_eq!(m.0.as_deref(), Some("")),
+        _ => panic!("expected unknown api error"),
+    }
+
+    // At least one code of every table.
+    let codes = [
+        (216, "login api error: incorrect captcha: bad captcha"),
+        (225, "profile api error: nickname is already taken: taken"),
+        (500, "trading api error: trading failed: unknown"),
+        (
+            1505,
+            "ragfair api error: not enough space in stash: no space",
+        ),
+        (
+            22002,
+            "inventory api error: item is already examined: examined",
+        ),
+        (207, "invalid request parameters: bad tm"),
+        (
+            602,
+            "not authorized or game profile not selected: session lost",
+        ),
+    ];
+    for (code, display) in codes.iter() {
+        let message = display.rsplit(": ").next().unwrap();
+        server.fail_once("/client/game/keepalive", *code, message);
+        let e = t.keep_alive().await.unwrap_err();
+        assert_eq!(e.to_string(), *display);
+        assert_eq!(e.message(), Some(message));
+    }
+
+    // Item moving errors are mapped with the same table.
+    server.respond(
+        "/client/game/profile/items/moving",
+        serde_json::json!({
+            "items": {},
+            "badRequest": [{ "err": 1516, "errmsg": "not enough items in stock" }]
+        }),
+    );
+    let res = t
+        .buy_item(
+            MOCK_OFFER_ID,
+            10,
+            &[BarterItem {
+                id: "5e8e1c8c9c2e6e1a5c3f1c01".to_string(),
+                count: 45000.0,
+            }],
+        )
+        .await;
+    match res {
+        Err(Error::RagfairError(RagfairError::InsufficientStock, m)) => {
+            assert_eq!(m.0.as_deref(), Some("not enough items in stock"))
+        }
+        _ => panic!("expected insufficient stock"),
+    }
+
+    // The API does not always say which action failed.
+    let mut batch = ActionBatch::new();
+    batch.examine("5e8e1c8c9c2e6e1a5c3f1c01");
+    let res = t.send_batch(&batch).await?;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(res.errors[0].index, None);
+    assert!(res.error(0).is_none());
+
+    // Several rejected actions are all reported with their index.
+    server.respond(
+        "/client/game/profile/items/moving",
+        serde_json::json!({
+            "items": {},
+            "badRequest": [
+                { "index": 0, "err": 1501, "errmsg": "max offer count" },
+                { "index": 1, "err": 1502, "errmsg": null }
+            ]
+        }),
+    );
+    let res = t
+        .offer_item(
+            &["5e8e1c8c9c2e6e1a5c3f1c02"],
+            &[Requirement {
+                schema_id: "5449016a4bdc2d6f028b456f".to_string(),
+                count: 2000.0,
+            }],
+            false,
+        )
+        .await;
+    match res {
+        Err(Error::ActionErrors(errors)) => {
+            assert_eq!(errors.0.len(), 2);
+            assert_eq!(errors.0[1].index, Some(1));
+            match &errors.0[1].error {
+                Error::RagfairError(RagfairError::InsufficientTaxFunds, _) => {}
+                _ => panic!("expected insufficient tax funds"),
+            }
+            assert_eq!(errors.0[0].error.message(), Some("max offer count"));
+        }
+        _ => panic!("expected action errors"),
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_retry() -> Result<()> {
+    let server = Arc::new(MockServer::new());
+    let t = Tarkov::builder()
+        .transport(server.clone())
+        .retry(RetryPolicy {
+            base_delay: Duration::from_millis(1),
+            ..RetryPolicy::default()
+        })
+        .from_session(MOCK_SESSION);
+
+    server
+        .fail_once("/client/game/keepalive", 1000, "backend error")
+        .status_once("/client/game/keepalive", http::StatusCode::BAD_GATEWAY);
+    t.keep_alive().await?;
+    assert_eq!(server.requests().len(), 3);
+
+    // Non-idempotent requests are not retried.
+    server.fail_once("/client/game/profile/items/moving", 1000, "backend error");
+    let res = t
+        .buy_item(
+            MOCK_OFFER_ID,
+            1,
+            &[BarterItem {
+                id: "5e8e1c8c9c2e6e1a5c3f1c01".to_string(),
+                count: 4500.0,
+            }],
+        )
+        .await;
+    match res {
+        Err(Error::BackendError(_)) => {}
+        _ => panic!("expected backend error"),
+    }
+    assert_eq!(server.requests().len(), 4);
+
+    // Errors that are not transient are surfaced immediately.
+    server.fail_once("/client/game/profile/list", 201, "not authorized");
+    match t.get_profiles().await {
+        Err(Error::NotAuthorized(_)) => {}
+        _ => panic!("expected not authorized"),
+    }
+    assert_eq!(server.requests().len(), 5);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_rate_limit() -> Result<()> {
+    tokio::time::pause();
+    // The limiter applies whatever the order of the builder calls.
+    let t = Tarkov::builder()
+        .rate_limit(RateLimiter::new().limit(
+            "prod.escapefromtarkov.com",
+            RateLimit {
+                requests_per_second: 20.0,
+                burst: 1,
+            },
+        ))
+        .transport(MockServer::new())
+        .from_session(MOCK_SESSION);
+
+    let start = tokio::time::Instant::now();
+    let limiter = t.rate_limiter().unwrap();
+    let depth = async {
+        tokio::time::delay_for(Duration::from_millis(10)).await;
+        limiter.queue_depth("https://prod.escapefromtarkov.com")
+    };
+    let (a, b, c, depth) = tokio::join!(t.keep_alive(), t.keep_alive(), t.keep_alive(), depth);
+    a?;
+    b?;
+    c?;
+
+    assert_eq!(depth, 2);
+    assert!(start.elapsed() >= Duration::from_millis(100));
+    assert_eq!(limiter.queue_depth("prod.escapefromtarkov.com"), 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_keep_alive_task() -> Result<()> {
+    // Time is paused and only moves to the next timer, so the steps below happen in a fixed
+    // order: keep-alive at 50ms, requests at 95-155ms, keep-alives at 205ms and 255ms.
+    tokio::time::pause();
+    let server = Arc::new(MockServer::new());
+    let mut t = mock_session(&server);
+    let keep_alive_count = || {
+        server
+            .requests()
+            .iter()
+            .filter(|r| r.url.ends_with("/client/game/keepalive"))
+            .count()
+    };
+
+    server.fail_once("/client/game/keepalive", 201, "not authorized");
+    let mut errors = t.spawn_keep_alive(Duration::from_millis(50));
+    tokio::time::delay_for(Duration::from_millis(75)).await;
+    match errors.try_recv() {
+        Ok(Error::NotAuthorized(_)) => {}
+        _ => panic!("expected keep-alive failure"),
+    }
+
+    // Other requests postpone the keep-alive.
+    for _ in 0..4 {
+        tokio::time::delay_for(Duration::from_millis(20)).await;
+        t.get_profiles().await?;
+    }
+    assert_eq!(keep_alive_count(), 1);
+
+    tokio::time::delay_for(Duration::from_millis(120)).await;
+    assert_eq!(keep_alive_count(), 3);
+
+    drop(t);
+    tokio::time::delay_for(Duration::from_millis(60)).await;
+    tokio::time::delay_for(Duration::from_millis(120)).await;
+    assert_eq!(keep_alive_count(), 3);
+    assert!(errors.recv().await.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_profile() -> Result<()> {
+    let server = Arc::new(MockServer::new());
+    let t = mock_session(&server);
+
+    t.keep_alive().await?;
+
+    let profiles = t.get_profiles().await?;
+    let profile = profiles
+        .into_iter()
+        .find(|p| p.info.side != Side::Savage)
+        .unwrap();
+    assert_eq!(profile.id, MOCK_PROFILE_ID);
+
+    t.select_profile(&profile.id).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_resume() -> Result<()> {
+    let server = Arc::new(MockServer::new());
+    let t = Tarkov::builder()
+        .transport(server.clone())
+        .login("me@example.com", "password", &generate_hwid())
+        .await?;
+    t.select_profile(MOCK_PROFILE_ID).await?;
+
+    let path = std::env::temp_dir().join(format!("tarkov-session-{}.json", std::process::id()));
+    // An existing file is restricted too.
+    std::fs::write(&path, "")?;
+    t.session_state().save(&path)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path)?.permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+    let state = SessionState::load(&path)?;
+    std::fs::remove_file(&path)?;
+    assert_eq!(state, t.session_state());
+    assert_eq!(state.profile_id.as_deref(), Some(MOCK_PROFILE_ID));
+    assert!(state.access_token.is_some());
+
+    let t = Tarkov::builder()
+        .transport(server.clone())
+        .resume(state.clone())
+        .await?;
+    assert_eq!(t.hwid(), state.hwid);
+    assert!(server
+        .requests()
+        .last()
+        .unwrap()
+        .url
+        .ends_with("/client/game/keepalive"));
+
+    // An expired session is exchanged for a new one and the profile selected again.
+    server.fail_once("/client/game/keepalive", 201, "not authorized");
+    let count = server.requests().len();
+    let _ = Tarkov::builder()
+        .transport(server.clone())
+        .resume(state.clone())
+        .await?;
+    let requests = server.requests();
+    assert_eq!(requests.len(), count + 4);
+    assert!(requests[count + 1].url.contains("/launcher/game/start"));
+    assert!(requests[count + 2]
+        .url
+        .ends_with("/client/game/profile/select"));
+    assert!(requests[count + 3].url.ends_with("/client/game/keepalive"));
+
+    server.fail_once("/client/game/keepalive", 201, "not authorized");
+    let res = Tarkov::builder()
+        .transport(server.clone())
+        .resume(SessionState {
+            access_token: None,
+            refresh_token: None,
+            ..state
+        })
+        .await;
+    match res {
+        Err(Error::NotAuthorized(_)) => {}
+        _ => panic!("expected not authorized"),
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_token_refresh() -> Result<()> {
+    let server = Arc::new(MockServer::new());
+    server.fail_once("/client/game/keepalive", 201, "not authorized");
+    let t = Tarkov::builder()
+        .transport(server.clone())
+        .resume(SessionState {
+            session: "expired-session".to_string(),
+            hwid: generate_hwid(),
+            profile_id: Some(MOCK_PROFILE_ID.to_string()),
+            access_token: Some("expired-access-token".to_string()),
+            refresh_token: Some(MOCK_REFRESH_TOKEN.to_string()),
+            expires_at: Some(0),
+            ..SessionState::default()
+        })
+        .await?;
+    assert!(!t.session_state().access_token_expired());
+    assert_eq!(
+        t.session_state().access_token.as_deref(),
+        Some(MOCK_ACCESS_TOKEN)
+    );
+
+    // Requests renew an expired session transparently.
+    server
+        .fail_once("/client/game/profile/list", 201, "not authorized")
+        .respond(
+            "/launcher/game/start",
+            serde_json::json!({ "queued": false, "session": "renewed-session" }),
+        );
+    let count = server.requests().len();
+    t.get_profiles().await?;
+    assert_eq!(t.session(), "renewed-session");
+
+    let requests = server.requests();
+    let paths: Vec<_> = requests[count..]
+        .iter()
+        .map(|r| r.url.split('?').next().unwrap())
+        .collect();
+    assert_eq!(
+        paths,
+        [
+            "https://prod.escapefromtarkov.com/client/game/profile/list",
+            "https://prod.escapefromtarkov.com/launcher/game/start",
+            "https://prod.escapefromtarkov.com/client/game/profile/select",
+            "https://prod.escapefromtarkov.com/client/game/profile/list",
+        ]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_pool() -> Result<()> {
+    let main = Arc::new(MockServer::new());
+    let alt = Arc::new(MockServer::new());
+    let mut pool = TarkovPool::new();
+    for (account, server) in [("main", &main), ("alt", &alt)].iter() {
+        let t = Tarkov::builder()
+            .transport((*server).clone())
+            .login("me@example.com", "password", &generate_hwid())
+            .await?;
+        t.select_profile(MOCK_PROFILE_ID).await?;
+        pool.add(account, t);
+    }
+    assert_eq!(pool.accounts(), ["main", "alt"]);
+
+    for _ in 0..4 {
+        pool.run(|t| Box::pin(t.keep_alive())).await?;
+    }
+    assert_eq!(main.requests().len(), 3 + 2);
+    assert_eq!(alt.requests().len(), 3 + 2);
+
+    // Expired sessions are renewed and the request sent again.
+    alt.fail_once("/client/game/profile/list", 201, "not authorized");
+    let profiles = pool.run_on("alt", |t| Box::pin(t.get_profiles())).await?;
+    assert_eq!(profiles[0].id, MOCK_PROFILE_ID);
+    assert!(alt.requests()[6].url.contains("/launcher/game/start"));
+    let starts = alt
+        .requests()
+        .iter()
+        .filter(|r| r.url.contains("/launcher/game/start"))
+        .count();
+    assert_eq!(starts, 2);
+
+    // Unhealthy accounts are skipped.
+    main.fail_once("/client/game/keepalive", 1000, "backend error");
+    assert!(pool
+        .run_on("main", |t| Box::pin(t.keep_alive()))
+        .await
+        .is_err());
+    let count = alt.requests().len();
+    pool.run(|t| Box::pin(t.keep_alive())).await?;
+    pool.run(|t| Box::pin(t.keep_alive())).await?;
+    assert_eq!(alt.requests().len(), count + 2);
+
+    let health = pool.health();
+    assert!(!health["main"].is_healthy());
+    assert_eq!(health["main"].failures, 1);
+    assert_eq!(health["alt"].requests, 5);
+    assert_eq!(health["alt"].reauthentications, 1);
+    assert!(health["alt"].is_healthy());
+
+    match pool.run_on("unknown", |t| Box::pin(t.keep_alive())).await {
+        Err(Error::UnknownAccount(account)) => assert_eq!(account, "unknown"),
+        _ => panic!("expected unknown account"),
+    }
+
+    // Failed renewals are not counted.
+    alt.fail_once("/client/game/profile/list", 201, "not authorized")
+        .fail_once("/launcher/game/start", 1000, "backend error");
+    assert!(pool
+        .run_on("alt", |t| Box::pin(t.get_profiles()))
+        .await
+        .is_err());
+    assert_eq!(pool.health()["alt"].reauthentications, 1);
+
+    match TarkovPool::new().run(|t| Box::pin(t.keep_alive())).await {
+        Err(Error::EmptyPool) => {}
+        _ => panic!("expected empty pool"),
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_traders() -> Result<()> {
+    let server = Arc::new(MockServer::new());
+    let t = mock_session(&server);
+
+    let traders = t.get_traders().await?;
+    assert_eq!(traders.len(), 1);
+    let trader = t.get_trader(MOCK_TRADER_ID).await?;
+    assert_eq!(trader.id, MOCK_TRADER_ID);
+
+    let items = t.get_trader_items(MOCK_TRADER_ID).await?;
+    assert_eq!(items.len(), 2);
+
+    let _ = t
+        .trade_item(
+            MOCK_TRADER_ID,
+            &items[0].id,
+            1,
+            &[BarterItem {
+                id: "5e8e1c8c9c2e6e1a5c3f1c01".to_string(),
+                count: 3990.0,
+            }],
+        )
+        .await?;
+    let _ = t
+        .sell_item(MOCK_TRADER_ID, "5e8e1c8c9c2e6e1a5c3f1c02", 1)
+        .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_malformed_responses() -> Result<()> {
+    let server = Arc::new(MockServer::new());
+    let t = mock_session(&server);
+
+    server.respond(
+        "/client/trading/api/getTradersList",
+        serde_json::Value::Null,
+    );
+    match t.get_traders().await {
+        Err(Error::MissingData) => {}
+        _ => panic!("expected missing data"),
+    }
+
+    let assort_path = format!("/client/trading/api/getTraderAssort/{}", MOCK_TRADER_ID);
+    let mut assort: serde_json::Value =
+        serde_json::from_str(include_str!("../src/mock/trader_assort.json"))?;
+    assort["loyal_level_items"]
+        .as_object_mut()
+        .unwrap()
+        .remove("5e8e1c8c9c2e6e1a5c3f1d02");
+    server.respond(&assort_path, assort);
+    match t.get_trader_items(MOCK_TRADER_ID).await {
+        Err(Error::MissingLoyaltyLevel(trader_id, item_id)) => {
+            assert_eq!(trader_id, MOCK_TRADER_ID);
+            assert_eq!(item_id, "5e8e1c8c9c2e6e1a5c3f1d02");
+        }
+        _ => panic!("expected missing loyalty level"),
+    }
+
+    let mut assort: serde_json::Value =
+        serde_json::from_str(include_str!("../src/mock/trader_assort.json"))?;
+    assort["barter_scheme"]["5e8e1c8c9c2e6e1a5c3f1d02"] = serde_json::json!([]);
+    server.respond(&assort_path, assort);
+    match t.get_trader_items(MOCK_TRADER_ID).await {
+        Err(Error::MissingPrice(_, item_id)) => assert_eq!(item_id, "5e8e1c8c9c2e6e1a5c3f1d02"),
+        _ => panic!("expected missing price"),
+    }
+
+    server.respond(
+        &format!(
+            "/client/trading/api/getUserAssortPrice/trader/{}",
+            MOCK_TRADER_ID
+        ),
+        serde_json::json!({}),
+    );
+    match t.get_trader_items(MOCK_TRADER_ID).await {
+        Err(Error::MissingPrice(_, item_id)) => assert_eq!(item_id, "5e8e1c8c9c2e6e1a5c3f1d01"),
+        _ => panic!("expected missing price"),
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_action_batch() -> Result<()> {
+    let server = Arc::new(MockServer::new());
+    let t = mock_session(&server);
+
+    server.respond(
+        "/client/game/profile/items/moving",
+        serde_json::json!({
+            "items": { "del": [{ "_id": "5e8e1c8c9c2e6e1a5c3f1c01" }] },
+            "badRequest": [{ "index": 1, "err": 513, "errmsg": "item sold" }]
+        }),
+    );
+
+    let mut batch = ActionBatch::new();
+    batch
+        .sell(MOCK_TRADER_ID, "5e8e1c8c9c2e6e1a5c3f1c01", 1)
+        .sell(MOCK_TRADER_ID, "5e8e1c8c9c2e6e1a5c3f1c02", 1)
+        .merge("5e8e1c8c9c2e6e1a5c3f1c03", "5e8e1c8c9c2e6e1a5c3f1c04");
+    let res = t.send_batch(&batch).await?;
+
+    let requests = server.requests();
+    let body = &requests.last().unwrap().body;
+    assert_eq!(body["data"].as_array().unwrap().len(), 3);
+    assert_eq!(body["data"][2]["Action"], "Merge");
+
+    assert!(res.error(0).is_none());
+    match res.error(1) {
+        Some(Error::TradingError(TradingError::ItemSold, _)) => {}
+        _ => panic!("expected item sold"),
+    }
+    assert_eq!(res.update.del.as_ref().unwrap().len(), 1);
+    match res.into_result() {
+        Err(Error::ActionErrors(errors)) => assert_eq!(errors.0[0].index, Some(1)),
+        _ => panic!("expected action errors"),
+    }
+
+    match t.send_batch(&ActionBatch::new()).await {
+        Err(Error::InvalidParameters) => {}
+        _ => panic!("expected invalid parameters"),
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_inventory_actions() -> Result<()> {
+    let server = Arc::new(MockServer::new());
+    let t = mock_session(&server);
+
+    let stash = Container::grid(
+        "5e8e1c8c9c2e6e1a5c3f1c00",
+        "hideout",
+        Location {
+            x: 2,
+            y: 3,
+            r: 1,
+            is_searched: None,
+        },
+    );
+    t.move_item("5e8e1c8c9c2e6e1a5c3f1c01", &stash).await?;
+    t.split_item("5e8e1c8c9c2e6e1a5c3f1c02", &stash, 10).await?;
+    t.move_item(
+        "5e8e1c8c9c2e6e1a5c3f1c03",
+        &Container::equipment("5e8e1c8c9c2e6e1a5c3f1cff", EquipmentSlot::Backpack),
+    )
+    .await?;
+
+    let requests = server.requests();
+    let bodies: Vec<_> = requests.iter().rev().take(3).map(|r| &r.body).collect();
+    assert_eq!(bodies[2]["data"][0]["Action"], "Move");
+    assert_eq!(bodies[2]["data"][0]["to"]["container"], "hideout");
+    assert_eq!(bodies[2]["data"][0]["to"]["location"]["x"], 2);
+    assert_eq!(bodies[1]["data"][0]["Action"], "Split");
+    assert_eq!(bodies[1]["data"][0]["count"], 10);
+    assert_eq!(bodies[0]["data"][0]["to"]["container"], "Backpack");
+    assert!(bodies[0]["data"][0]["to"].get("location").is_none());
+
+    match t.split_item("5e8e1c8c9c2e6e1a5c3f1c02", &stash, 0).await {
+        Err(Error::InvalidParameters) => {}
+        _ => panic!("expected invalid parameters"),
+    }
+
+    // Equipment slots are sent with their API name.
+    let slots = [
+        (EquipmentSlot::FirstPrimaryWeapon, "FirstPrimaryWeapon"),
+        (EquipmentSlot::Holster, "Holster"),
+        (EquipmentSlot::TacticalVest, "TacticalVest"),
+        (EquipmentSlot::SecuredContainer, "SecuredContainer"),
+    ];
+    for (slot, name) in slots.iter() {
+        let to = Container::equipment("5e8e1c8c9c2e6e1a5c3f1cff", *slot);
+        t.move_item("5e8e1c8c9c2e6e1a5c3f1c04", &to).await?;
+        let body = server.requests().last().unwrap().body.clone();
+        assert_eq!(body["data"][0]["to"]["id"], "5e8e1c8c9c2e6e1a5c3f1cff");
+        assert_eq!(body["data"][0]["to"]["container"], *name);
+    }
+
+    let scope = Container::slot("5e8e1c8c9c2e6e1a5c3f1c04", "mod_scope")?;
+    t.move_item("5e8e1c8c9c2e6e1a5c3f1c05", &scope).await?;
+    let body = server.requests().last().unwrap().body.clone();
+    assert_eq!(body["data"][0]["to"]["container"], "mod_scope");
+    match Container::slot("5e8e1c8c9c2e6e1a5c3f1c00", "hideout") {
+        Err(Error::InvalidParameters) => {}
+        _ => panic!("expected invalid parameters"),
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_transaction_sequence() -> Result<()> {
+    let server = Arc::new(MockServer::new());
+    let t = mock_session(&server);
+
+    let (a, b, c) = tokio::join!(
+        t.sell_item(MOCK_TRADER_ID, "5e8e1c8c9c2e6e1a5c3f1c01", 1),
+        t.sell_item(MOCK_TRADER_ID, "5e8e1c8c9c2e6e1a5c3f1c02", 1),
+        t.search_market(0, 15, MarketFilter::default()),
+    );
+    a?;
+    b?;
+    c?;
+
+    let mut batch = ActionBatch::new();
+    batch.merge("5e8e1c8c9c2e6e1a5c3f1c01", "5e8e1c8c9c2e6e1a5c3f1c02");
+    assert_eq!(t.send_batch(&batch).await?.tm, 4);
+
+    let mut tms: Vec<u64> = server
+        .requests()
+        .iter()
+        .filter_map(|r| r.body["tm"].as_u64())
+        .collect();
+    tms.sort();
+    assert_eq!(tms, vec![1, 2, 3, 4]);
+
+    // Item moving requests reach the server in transaction order.
+    let moving: Vec<u64> = server
+        .requests()
+        .iter()
+        .filter(|r| r.url.contains("/items/moving"))
+        .filter_map(|r| r.body["tm"].as_u64())
+        .collect();
+    assert!(moving.windows(2).all(|w| w[0] < w[1]));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_flea_market() -> Result<()> {
+    let server = Arc::new(MockServer::new());
+    let t = mock_session(&server);
+
+    let offers = t.search_market(0, 15, MarketFilter::default()).await?;
+    assert_eq!(offers.offers[0].id, MOCK_OFFER_ID);
+
+    let price = t.get_item_price("544fb37f4bdc2dee738b4567").await?;
+    assert_eq!(price.avg, 4500.0);
+
+    let _ = t
+        .buy_item(
+            MOCK_OFFER_ID,
+            1,
+            &[BarterItem {
+                id: "5e8e1c8c9c2e6e1a5c3f1c01".to_string(),
+                count: 4500.0,
+            }],
+        )
+        .await?;
+    let _ = t
+        .offer_item(
+            &["5e8e1c8c9c2e6e1a5c3f1c02"],
+            &[Requirement {
+                schema_id: "5449016a4bdc2d6f028b456f".to_string(),
+                count: 2000.0,
+            }],
+            false,
+        )
+        .await?;
+
+    Ok(())
+}
+
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_record_replay() -> Result<()> {
+    let buffer = SharedBuffer::default();
+    // The recorder applies whatever the order of the builder calls.
+    let t = Tarkov::builder()
+        .record(buffer.clone())
+        .transport(MockServer::new())
+        .from_session(MOCK_SESSION);
+
+    let profiles = t.get_profiles().await?;
+    t.select_profile(MOCK_PROFILE_ID).await?;
+    t.keep_alive().await?;
+
+    let recording = buffer.0.lock().unwrap().clone();
+    assert_eq!(recording.iter().filter(|b| **b == b'\n').count(), 3);
+
+    let replay = Arc::new(Replay::from_reader(recording.as_slice())?);
+    let t = Tarkov::builder()
+        .transport(replay.clone())
+        .from_session(MOCK_SESSION);
+
+    assert_eq!(t.get_profiles().await?, profiles);
+    t.select_profile(MOCK_PROFILE_ID).await?;
+    t.keep_alive().await?;
+    assert_eq!(replay.remaining(), 0);
+
+    match t.keep_alive().await {
+        Err(Error::Replay(_)) => {}
+        _ => panic!("expected recording to be exhausted"),
+    }
+
+    Ok(())
+}
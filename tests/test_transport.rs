@@ -0,0 +1,87 @@
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use http::{Request, Response, StatusCode};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use tarkov::config::ClientConfig;
+use tarkov::transport::{Transport, TransportFuture};
+use tarkov::{Error, Result, Tarkov};
+
+struct StubTransport {
+    status: StatusCode,
+    body: &'static str,
+    urls: Mutex<Vec<String>>,
+}
+
+impl Transport for StubTransport {
+    fn send(&self, request: Request<Vec<u8>>) -> TransportFuture<'_> {
+        self.urls.lock().unwrap().push(request.uri().to_string());
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(self.body.as_bytes()).unwrap();
+        let body = encoder.finish().unwrap();
+        let res = Response::builder().status(self.status).body(body).unwrap();
+
+        Box::pin(async move { Ok(res) })
+    }
+}
+
+#[tokio::test]
+async fn test_custom_transport() -> Result<()> {
+    let transport = Arc::new(StubTransport {
+        status: StatusCode::OK,
+        body: r#"{"err":0,"errmsg":null,"data":null}"#,
+        urls: Mutex::new(Vec::new()),
+    });
+    let t = Tarkov::builder()
+        .transport(transport.clone())
+        .from_session("e1bc65a216325f0ad0db8518fa299db2");
+
+    t.keep_alive().await?;
+    assert_eq!(
+        *transport.urls.lock().unwrap(),
+        vec!["https://prod.escapefromtarkov.com/client/game/keepalive"]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_custom_config() -> Result<()> {
+    let transport = Arc::new(StubTransport {
+        status: StatusCode::OK,
+        body: r#"{"err":0,"errmsg":null,"data":null}"#,
+        urls: Mutex::new(Vec::new()),
+    });
+    let t = Tarkov::builder()
+        .transport(transport.clone())
+        .config(ClientConfig {
+            prod_endpoint: "http://127.0.0.1:8080".to_string(),
+            ..ClientConfig::default()
+        })
+        .from_session("e1bc65a216325f0ad0db8518fa299db2");
+
+    t.keep_alive().await?;
+    assert_eq!(
+        *transport.urls.lock().unwrap(),
+        vec!["http://127.0.0.1:8080/client/game/keepalive"]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_custom_transport_status() {
+    let t = Tarkov::builder()
+        .transport(StubTransport {
+            status: StatusCode::BAD_GATEWAY,
+            body: "",
+            urls: Mutex::new(Vec::new()),
+        })
+        .from_session("e1bc65a216325f0ad0db8518fa299db2");
+
+    match t.keep_alive().await {
+        Err(Error::Status(StatusCode::BAD_GATEWAY)) => {}
+        res => panic!("unexpected result: {:?}", res),
+    }
+}